@@ -1,8 +1,5 @@
 extern crate nrf24l01;
 
-use std::thread::sleep;
-use std::time::Duration;
-
 use nrf24l01::{OperatingMode, PALevel, RXConfig, NRF24L01};
 
 fn main() {
@@ -16,14 +13,8 @@ fn main() {
     device.configure(&OperatingMode::RX(config)).unwrap();
     device.listen().unwrap();
     loop {
-        sleep(Duration::from_millis(500));
-        if device.data_available().unwrap() {
-            device
-                .read_all(|packet| {
-                    println!("Received {:?} bytes", packet.len());
-                    println!("Payload {:?}", packet);
-                })
-                .unwrap();
-        }
+        let packet = device.recv().unwrap();
+        println!("Received {:?} bytes", packet.payload().len());
+        println!("Payload {:?}", packet.payload());
     }
 }
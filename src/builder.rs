@@ -0,0 +1,390 @@
+// Copyright 2017, Romuald Texier-Marcadé <romualdtm@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Validating builders for the configuration types.
+//
+// Contrary to `configure`, which silently caps out of range values, the builders
+// reject them with a descriptive error.
+
+use std::io;
+
+use crate::{
+    Channel, DataRate, Interrupts, OperatingMode, PALevel, RXConfig, RetryDelay, TXConfig,
+};
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn check_address(name: &str, address: [u8; 5]) -> io::Result<()> {
+    // Such addresses are easily detected in noise or mistaken for the preamble, p 25
+    let first = address[0];
+    if address.iter().all(|&byte| byte == first) && [0x00, 0xFF, 0x55, 0xAA].contains(&first) {
+        Err(invalid(format!(
+            "{} address {:02X?} is not valid, it can be mistaken for noise or preamble",
+            name, address
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+impl RXConfig {
+    /// Start building a receiver configuration, checked by `.build()`.
+    ///
+    /// ```rust
+    /// use nrf24l01::{PALevel, RXConfig};
+    ///
+    /// let config = RXConfig::builder()
+    ///     .channel(108)
+    ///     .pa_level(PALevel::Low)
+    ///     .pipe0_address(*b"abcde")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(config.channel, 108);
+    /// ```
+    pub fn builder() -> RXConfigBuilder {
+        RXConfigBuilder {
+            config: RXConfig::default(),
+        }
+    }
+
+    /// Check the configuration is complete and all its values are in range.
+    ///
+    /// # Errors
+    ///
+    /// A custom io::ErrorKind::InvalidInput describing the first problem found.
+    pub fn validate(&self) -> io::Result<()> {
+        Channel::new(self.channel)?;
+        check_address("Pipe 0", self.pipe0_address)?;
+        let mut addresses = vec![(0, self.pipe0_address)];
+        if let Some(address) = self.pipe1_address {
+            check_address("Pipe 1", address)?;
+            addresses.push((1, address));
+        }
+        let lsbs = [
+            self.pipe2_addr_lsb,
+            self.pipe3_addr_lsb,
+            self.pipe4_addr_lsb,
+            self.pipe5_addr_lsb,
+        ];
+        for (pipe, lsb) in (2..).zip(lsbs.iter()) {
+            if let Some(lsb) = *lsb {
+                match self.pipe1_address {
+                    Some(pipe1_address) => {
+                        let mut address = pipe1_address;
+                        address[0] = lsb;
+                        addresses.push((pipe, address));
+                    }
+                    None => {
+                        return Err(invalid(format!(
+                            "Pipe {} requires pipe 1, whose 4 most significant bytes it shares",
+                            pipe
+                        )))
+                    }
+                }
+            }
+        }
+        for (index, &(pipe, address)) in addresses.iter().enumerate() {
+            if let Some(&(other, _)) = addresses[..index].iter().find(|p| p.1 == address) {
+                return Err(invalid(format!(
+                    "Pipes {} and {} have the same address {:02X?}",
+                    other, pipe, address
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for `RXConfig`, see `RXConfig::builder()`.
+#[derive(Debug, Clone)]
+pub struct RXConfigBuilder {
+    config: RXConfig,
+}
+
+impl RXConfigBuilder {
+    /// Set the data rate.
+    pub fn data_rate(mut self, data_rate: DataRate) -> Self {
+        self.config.data_rate = data_rate;
+        self
+    }
+
+    /// Set the channel, either a raw value in the range [0, 125] or a `Channel`.
+    pub fn channel<C: Into<u8>>(mut self, channel: C) -> Self {
+        self.config.channel = channel.into();
+        self
+    }
+
+    /// Set the power level.
+    pub fn pa_level(mut self, pa_level: PALevel) -> Self {
+        self.config.pa_level = pa_level;
+        self
+    }
+
+    /// Set the LNA_HCURR bit for a higher LNA gain (nRF24L01 only).
+    pub fn lna_gain(mut self, lna_gain: bool) -> Self {
+        self.config.lna_gain = lna_gain;
+        self
+    }
+
+    /// Set the pipe 0 address (mandatory).
+    pub fn pipe0_address(mut self, address: [u8; 5]) -> Self {
+        self.config.pipe0_address = address;
+        self
+    }
+
+    /// Enable pipe 1.
+    pub fn pipe1_address(mut self, address: [u8; 5]) -> Self {
+        self.config.pipe1_address = Some(address);
+        self
+    }
+
+    /// Enable pipe 2, requires pipe 1.
+    pub fn pipe2_addr_lsb(mut self, lsb: u8) -> Self {
+        self.config.pipe2_addr_lsb = Some(lsb);
+        self
+    }
+
+    /// Enable pipe 3, requires pipe 1.
+    pub fn pipe3_addr_lsb(mut self, lsb: u8) -> Self {
+        self.config.pipe3_addr_lsb = Some(lsb);
+        self
+    }
+
+    /// Enable pipe 4, requires pipe 1.
+    pub fn pipe4_addr_lsb(mut self, lsb: u8) -> Self {
+        self.config.pipe4_addr_lsb = Some(lsb);
+        self
+    }
+
+    /// Enable pipe 5, requires pipe 1.
+    pub fn pipe5_addr_lsb(mut self, lsb: u8) -> Self {
+        self.config.pipe5_addr_lsb = Some(lsb);
+        self
+    }
+
+    /// Set the events reflected on the IRQ pin, RX_DR only by default.
+    pub fn interrupts(mut self, interrupts: Interrupts) -> Self {
+        self.config.interrupts = Some(interrupts);
+        self
+    }
+
+    /// Check and return the configuration, see `RXConfig::validate()`.
+    pub fn build(self) -> io::Result<RXConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+impl TXConfig {
+    /// Start building a transmitter configuration, checked by `.build()`.
+    ///
+    /// ```rust
+    /// use nrf24l01::TXConfig;
+    ///
+    /// let config = TXConfig::builder()
+    ///     .channel(108)
+    ///     .pipe0_address(*b"abcde")
+    ///     .max_retries(3)
+    ///     .retry_delay(2)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(config.max_retries, 3);
+    /// ```
+    pub fn builder() -> TXConfigBuilder {
+        TXConfigBuilder {
+            config: TXConfig::default(),
+        }
+    }
+
+    /// Check the configuration is complete and all its values are in range.
+    ///
+    /// # Errors
+    ///
+    /// A custom io::ErrorKind::InvalidInput describing the first problem found.
+    pub fn validate(&self) -> io::Result<()> {
+        Channel::new(self.channel)?;
+        check_address("Pipe 0", self.pipe0_address)?;
+        if self.max_retries > 15 {
+            return Err(invalid(format!(
+                "Max retries {} is out of range [0, 15]",
+                self.max_retries
+            )));
+        }
+        RetryDelay::new(self.retry_delay)?;
+        if self.max_ack_payload > 32 {
+            return Err(invalid(format!(
+                "Max ACK payload {} is out of range [0, 32]",
+                self.max_ack_payload
+            )));
+        }
+        if !self.auto_retry_delay && self.retry_delay < self.min_retry_delay().value() {
+            return Err(self.retry_delay_error());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn retry_delay_error(&self) -> io::Error {
+        invalid(format!(
+            "Retry delay {} µs is too short for {} bytes ACK payloads, use at least {} µs",
+            250 + 250 * u32::from(self.retry_delay),
+            self.max_ack_payload,
+            self.min_retry_delay().micros()
+        ))
+    }
+}
+
+impl OperatingMode {
+    /// Check the configuration of the mode, see `RXConfig::validate()` and
+    /// `TXConfig::validate()`.
+    pub fn validate(&self) -> io::Result<()> {
+        match *self {
+            OperatingMode::RX(ref config) => config.validate(),
+            OperatingMode::TX(ref config) => config.validate(),
+        }
+    }
+}
+
+/// Builder for `TXConfig`, see `TXConfig::builder()`.
+#[derive(Debug, Clone)]
+pub struct TXConfigBuilder {
+    config: TXConfig,
+}
+
+impl TXConfigBuilder {
+    /// Set the data rate.
+    pub fn data_rate(mut self, data_rate: DataRate) -> Self {
+        self.config.data_rate = data_rate;
+        self
+    }
+
+    /// Set the channel, either a raw value in the range [0, 125] or a `Channel`.
+    pub fn channel<C: Into<u8>>(mut self, channel: C) -> Self {
+        self.config.channel = channel.into();
+        self
+    }
+
+    /// Set the power level.
+    pub fn pa_level(mut self, pa_level: PALevel) -> Self {
+        self.config.pa_level = pa_level;
+        self
+    }
+
+    /// Set the LNA_HCURR bit for a higher LNA gain (nRF24L01 only).
+    pub fn lna_gain(mut self, lna_gain: bool) -> Self {
+        self.config.lna_gain = lna_gain;
+        self
+    }
+
+    /// Set the max number of retries, in the range [0, 15].
+    pub fn max_retries(mut self, max_retries: u8) -> Self {
+        self.config.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay between retries, either a raw value in the range [0, 15]
+    /// or a `RetryDelay`.
+    pub fn retry_delay<D: Into<u8>>(mut self, retry_delay: D) -> Self {
+        self.config.retry_delay = retry_delay.into();
+        self
+    }
+
+    /// Use the smallest safe retry delay, see `TXConfig::min_retry_delay()`.
+    pub fn auto_retry_delay(mut self) -> Self {
+        self.config.auto_retry_delay = true;
+        self
+    }
+
+    /// Set the size of the largest ACK payload expected, in the range [0, 32].
+    pub fn max_ack_payload(mut self, max_ack_payload: u8) -> Self {
+        self.config.max_ack_payload = max_ack_payload;
+        self
+    }
+
+    /// Set the destination address (mandatory).
+    pub fn pipe0_address(mut self, address: [u8; 5]) -> Self {
+        self.config.pipe0_address = address;
+        self
+    }
+
+    /// Set the events reflected on the IRQ pin, TX_DS and MAX_RT by default.
+    pub fn interrupts(mut self, interrupts: Interrupts) -> Self {
+        self.config.interrupts = Some(interrupts);
+        self
+    }
+
+    /// Check and return the configuration, see `TXConfig::validate()`.
+    pub fn build(self) -> io::Result<TXConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rx_builder() {
+        let rx_conf = RXConfig::builder()
+            .channel(108)
+            .pipe0_address(*b"0node")
+            .pipe1_address(*b"1node")
+            .pipe2_addr_lsb(b'2')
+            .build()
+            .unwrap();
+        assert_eq!(rx_conf.channel, 108);
+        assert_eq!(rx_conf.pipe2_addr_lsb, Some(b'2'));
+    }
+
+    #[test]
+    fn tx_builder_units() {
+        let tx_conf = TXConfig::builder()
+            .channel(Channel::from_mhz(2476).unwrap())
+            .retry_delay(RetryDelay::from_micros(750).unwrap())
+            .pipe0_address(*b"abcde")
+            .build()
+            .unwrap();
+        assert_eq!(tx_conf.channel, 76);
+        assert_eq!(tx_conf.retry_delay, 2);
+    }
+
+    #[test]
+    fn rx_builder_errors() {
+        let builder = RXConfig::builder().pipe0_address(*b"0node");
+        assert!(builder.clone().channel(126).build().is_err());
+        assert!(RXConfig::builder().build().is_err());
+        assert!(builder.clone().pipe3_addr_lsb(b'3').build().is_err());
+        assert!(builder
+            .pipe1_address(*b"1node")
+            .pipe2_addr_lsb(b'1')
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn tx_builder_errors() {
+        let builder = TXConfig::builder().pipe0_address(*b"abcde");
+        assert!(builder.clone().build().is_ok());
+        assert!(builder.clone().max_retries(16).build().is_err());
+        assert!(builder.clone().retry_delay(16).build().is_err());
+        assert!(builder.clone().max_ack_payload(33).build().is_err());
+        assert!(builder
+            .clone()
+            .data_rate(DataRate::R250Kbps)
+            .retry_delay(0)
+            .build()
+            .is_err());
+        assert!(builder
+            .data_rate(DataRate::R250Kbps)
+            .auto_retry_delay()
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright 2017, Romuald Texier-Marcadé <romualdtm@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Serde helpers for the configuration types, and loading from TOML or JSON files.
+//
+// The schema is meant to be written by hand:
+//
+// mode = "tx"
+// data_rate = "250kbps"
+// channel_mhz = 2508
+// pa_level = "low"
+// pipe0_address = "abcde"
+// max_retries = 3
+// retry_delay_us = 750
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Serialize, Serializer};
+
+use crate::{Channel, OperatingMode, RetryDelay};
+
+// Addresses are written either as a 5 characters string or as an array of 5 bytes.
+pub mod address {
+    use super::*;
+
+    struct AddressVisitor;
+
+    impl<'de> Visitor<'de> for AddressVisitor {
+        type Value = [u8; 5];
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a 5 characters string or an array of 5 bytes")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<[u8; 5], E> {
+            let mut address = [0u8; 5];
+            if value.len() != 5 {
+                return Err(E::invalid_length(value.len(), &self));
+            }
+            address.copy_from_slice(value.as_bytes());
+            Ok(address)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; 5], A::Error> {
+            let mut address = [0u8; 5];
+            for (index, byte) in address.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(index, &self))?;
+            }
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(de::Error::invalid_length(6, &self));
+            }
+            Ok(address)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(address: &[u8; 5], serializer: S) -> Result<S::Ok, S::Error> {
+        match std::str::from_utf8(address) {
+            Ok(text) if address.iter().all(u8::is_ascii_graphic) => serializer.serialize_str(text),
+            _ => address.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 5], D::Error> {
+        deserializer.deserialize_any(AddressVisitor)
+    }
+}
+
+// Same as `address`, for the optional pipe 1 address.
+pub mod optional_address {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        address: &Option<[u8; 5]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match *address {
+            Some(ref address) => super::address::serialize(address, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[u8; 5]>, D::Error> {
+        super::address::deserialize(deserializer).map(Some)
+    }
+}
+
+// The retry delay is written in µs rather than as the raw register value.
+pub mod retry_delay_micros {
+    use super::*;
+    use serde::Deserialize;
+
+    pub fn serialize<S: Serializer>(retry_delay: &u8, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(250 + 250 * u32::from((*retry_delay).min(15)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+        let micros = u32::deserialize(deserializer)?;
+        RetryDelay::from_micros(micros)
+            .map(RetryDelay::value)
+            .map_err(de::Error::custom)
+    }
+}
+
+fn invalid_data<E: fmt::Display>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+// Replace the channel written as a frequency, `channel_mhz`, by the raw `channel`.
+fn channel_from_mhz(mut value: serde_json::Value) -> io::Result<serde_json::Value> {
+    if let Some(fields) = value.as_object_mut() {
+        if let Some(mhz) = fields.remove("channel_mhz") {
+            if fields.contains_key("channel") {
+                return Err(invalid_data("channel and channel_mhz are both set"));
+            }
+            let mhz = mhz
+                .as_u64()
+                .and_then(|mhz| u16::try_from(mhz).ok())
+                .ok_or_else(|| invalid_data("channel_mhz must be a frequency in MHz"))?;
+            let channel = Channel::from_mhz(mhz)?;
+            fields.insert("channel".to_string(), channel.value().into());
+        }
+    }
+    Ok(value)
+}
+
+// Deserialize an operating mode once the units have been converted.
+fn parse(value: serde_json::Value) -> io::Result<OperatingMode> {
+    let mode: OperatingMode =
+        serde_json::from_value(channel_from_mhz(value)?).map_err(invalid_data)?;
+    mode.validate()?;
+    Ok(mode)
+}
+
+impl OperatingMode {
+    /// Load and validate an operating mode from a TOML (`.toml`) or JSON (`.json`) file.
+    ///
+    /// ```toml
+    /// mode = "rx"
+    /// channel = 108
+    /// pa_level = "low"
+    /// pipe0_address = "abcde"
+    /// ```
+    ///
+    /// Addresses are written as 5 characters strings or arrays of 5 bytes, and the
+    /// retry delay in µs (`retry_delay_us`). The channel is written either as its raw
+    /// value (`channel`) or as its frequency in MHz (`channel_mhz`). Omitted fields take
+    /// their default value.
+    ///
+    /// # Errors
+    ///
+    /// System IO errors, a custom io::ErrorKind::InvalidData if the file can't be parsed
+    /// and a custom io::ErrorKind::InvalidInput if the configuration is not valid.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<OperatingMode> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => OperatingMode::from_toml(&content),
+            Some("json") => OperatingMode::from_json(&content),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown configuration file format: {}", path.display()),
+            )),
+        }
+    }
+
+    /// Parse and validate an operating mode written in TOML.
+    pub fn from_toml(content: &str) -> io::Result<OperatingMode> {
+        parse(toml::from_str(content).map_err(invalid_data)?)
+    }
+
+    /// Parse and validate an operating mode written in JSON.
+    pub fn from_json(content: &str) -> io::Result<OperatingMode> {
+        parse(serde_json::from_str(content).map_err(invalid_data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DataRate, Interrupts, OperatingMode, PALevel, TXConfig};
+
+    #[test]
+    fn rx_from_toml() {
+        let content = r#"
+            mode = "rx"
+            channel = 108
+            pa_level = "low"
+            pipe0_address = "0node"
+            pipe1_address = [49, 110, 111, 100, 101]
+            pipe2_addr_lsb = 50
+            interrupts = { rx_dr = true, tx_ds = true }
+        "#;
+        match OperatingMode::from_toml(content).unwrap() {
+            OperatingMode::RX(config) => {
+                assert_eq!(config.channel, 108);
+                assert_eq!(config.pa_level, PALevel::Low);
+                assert_eq!(config.data_rate, DataRate::R1Mbps);
+                assert_eq!(config.pipe0_address, *b"0node");
+                assert_eq!(config.pipe1_address, Some(*b"1node"));
+                assert_eq!(config.pipe2_addr_lsb, Some(b'2'));
+                assert_eq!(
+                    config.interrupts,
+                    Some(Interrupts {
+                        rx_dr: true,
+                        tx_ds: true,
+                        max_rt: false
+                    })
+                );
+            }
+            OperatingMode::TX(_) => panic!("RX mode expected"),
+        }
+    }
+
+    #[test]
+    fn tx_from_json() {
+        let content = r#"{
+            "mode": "tx",
+            "data_rate": "250kbps",
+            "pipe0_address": "abcde",
+            "max_retries": 3,
+            "retry_delay_us": 750,
+            "channel_mhz": 2508
+        }"#;
+        match OperatingMode::from_json(content).unwrap() {
+            OperatingMode::TX(config) => {
+                assert_eq!(config.data_rate, DataRate::R250Kbps);
+                assert_eq!(config.max_retries, 3);
+                assert_eq!(config.retry_delay, 2);
+                assert_eq!(config.channel, 108);
+            }
+            OperatingMode::RX(_) => panic!("TX mode expected"),
+        }
+    }
+
+    #[test]
+    fn invalid_files() {
+        // not a multiple of 250 µs
+        assert!(OperatingMode::from_toml(
+            "mode = \"tx\"\npipe0_address = \"abcde\"\nretry_delay_us = 700"
+        )
+        .is_err());
+        // typo
+        assert!(
+            OperatingMode::from_toml("mode = \"rx\"\npipe0_address = \"abcde\"\nchanel = 1")
+                .is_err()
+        );
+        // out of range
+        assert!(OperatingMode::from_toml(
+            "mode = \"rx\"\npipe0_address = \"abcde\"\nchannel = 126"
+        )
+        .is_err());
+        // both forms of the channel
+        assert!(OperatingMode::from_toml(
+            "mode = \"rx\"\npipe0_address = \"abcde\"\nchannel = 1\nchannel_mhz = 2401"
+        )
+        .is_err());
+        // not a 2.4 GHz frequency
+        assert!(OperatingMode::from_toml(
+            "mode = \"rx\"\npipe0_address = \"abcde\"\nchannel_mhz = 5800"
+        )
+        .is_err());
+        // address too short
+        assert!(OperatingMode::from_toml("mode = \"rx\"\npipe0_address = \"abcd\"").is_err());
+    }
+
+    #[test]
+    fn tx_round_trip() {
+        let mode = OperatingMode::TX(TXConfig {
+            channel: 76,
+            pipe0_address: [0xE7, 0xE7, 0xE7, 0xE7, 0x01],
+            retry_delay: 5,
+            ..Default::default()
+        });
+        let content = toml::to_string(&mode).unwrap();
+        assert!(content.contains("retry_delay_us = 1500"));
+        match OperatingMode::from_toml(&content).unwrap() {
+            OperatingMode::TX(config) => {
+                assert_eq!(config.channel, 76);
+                assert_eq!(config.pipe0_address, [0xE7, 0xE7, 0xE7, 0xE7, 0x01]);
+                assert_eq!(config.retry_delay, 5);
+            }
+            OperatingMode::RX(_) => panic!("TX mode expected"),
+        }
+    }
+}
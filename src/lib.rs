@@ -112,38 +112,57 @@
 //! ```
 
 extern crate spidev;
+mod background;
+mod builder;
+#[cfg(feature = "serde")]
+mod config_file;
+mod regulatory;
 #[cfg(feature = "rpi_accel")]
 mod rpi_ce;
+mod shared;
 #[cfg(not(feature = "rpi_accel"))]
 mod sysfs_ce;
+pub mod typestate;
+mod units;
 
+pub use background::BackgroundReceiver;
+pub use builder::{RXConfigBuilder, TXConfigBuilder};
+pub use regulatory::RegulatoryProfile;
+pub use shared::RadioHandle;
+pub use units::{Channel, RetryDelay};
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
 use std::io;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "rpi_accel")]
-use rpi_ce::CEPin;
+use rpi_ce::{CEPin, IrqPin};
 #[cfg(not(feature = "rpi_accel"))]
-use sysfs_ce::CEPin;
+use sysfs_ce::{CEPin, IrqPin};
 
 /// Supported air data rates.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataRate {
+    #[cfg_attr(feature = "serde", serde(rename = "250kbps"))]
     R250Kbps,
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "1mbps"))]
     R1Mbps,
+    #[cfg_attr(feature = "serde", serde(rename = "2mbps"))]
     R2Mbps,
 }
 
-impl Default for DataRate {
-    fn default() -> DataRate {
-        DataRate::R1Mbps
-    }
-}
-
 /// Supported power amplifier levels.
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum PALevel {
     /// -18 dBm, 7.0 mA DC current consumption, few meters range.
+    #[default]
     Min,
     /// -12 dBM, 7.5 mA DC current consumption.
     Low,
@@ -153,14 +172,61 @@ pub enum PALevel {
     Max,
 }
 
-impl Default for PALevel {
-    fn default() -> PALevel {
-        PALevel::Min
+/// Events reflected on the IRQ pin (active low).
+///
+/// The driver polls the STATUS register, unless the IRQ pin is wired (see
+/// `NRF24L01::set_irq_pin`) and RX_DR is reflected on it: then the functions waiting
+/// for incoming packets wait for the IRQ. The events can be chosen freely, for instance
+/// to wake up a MCU only on incoming data.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Interrupts {
+    /// Data received (RX_DR), defaults to false.
+    pub rx_dr: bool,
+    /// Data sent, that is acknowledged (TX_DS), defaults to false.
+    pub tx_ds: bool,
+    /// Max number of retries reached (MAX_RT), defaults to false.
+    pub max_rt: bool,
+}
+
+impl Interrupts {
+    /// No event asserts the IRQ pin.
+    pub const NONE: Interrupts = Interrupts {
+        rx_dr: false,
+        tx_ds: false,
+        max_rt: false,
+    };
+
+    /// All events assert the IRQ pin.
+    pub const ALL: Interrupts = Interrupts {
+        rx_dr: true,
+        tx_ds: true,
+        max_rt: true,
+    };
+
+    // MASK_RX_DR, MASK_TX_DS and MASK_MAX_RT bits of CONFIG
+    fn mask_bits(self) -> u8 {
+        let mut mask = 0b0111_0000;
+        if self.rx_dr {
+            mask &= !0b0100_0000;
+        }
+        if self.tx_ds {
+            mask &= !0b0010_0000;
+        }
+        if self.max_rt {
+            mask &= !0b0001_0000;
+        }
+        mask
     }
 }
 
 /// Receiver mode configuration
-#[derive(Debug, Default)]
+///
+/// Use `RXConfig::builder()` to have invalid values reported instead of capped.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
 pub struct RXConfig {
     /// data rate, defaults to `DataRate::R1Mbps`.
     pub data_rate: DataRate,
@@ -171,6 +237,8 @@ pub struct RXConfig {
     /// F = 2400 + `channel` Mhz
     ///
     /// Any `channel` value above 125 is capped to 125.
+    ///
+    /// See `Channel` to set it from a frequency.
     pub channel: u8,
     /// Powel level, defaults to `PALevel::Min`.
     pub pa_level: PALevel,
@@ -185,25 +253,48 @@ pub struct RXConfig {
     /// The address is in little endian order: the first byte is the least significant one.
     ///
     /// You must provide a valid address for Pipe 0.
+    #[cfg_attr(feature = "serde", serde(with = "config_file::address"))]
     pub pipe0_address: [u8; 5],
     /// Pipe 1 address, defaults to None (disabled)
     ///
     /// All pipes 2-5 share the 4 most significant bytes with the pipe 1 address, so
     /// you only need to provide the least significant byte to enable one of those pipes or
     /// set it to None to disable it (default).
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "config_file::optional_address",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
     pub pipe1_address: Option<[u8; 5]>,
     /// Pipe 2 LSB, defaults to None (disabled)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub pipe2_addr_lsb: Option<u8>,
     /// Pipe 3 LSB, defaults to None (disabled)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub pipe3_addr_lsb: Option<u8>,
     /// Pipe 4 LSB, defaults to None (disabled)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub pipe4_addr_lsb: Option<u8>,
     /// Pipe 5 LSB, defaults to None (disabled)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub pipe5_addr_lsb: Option<u8>,
+    /// Set the LNA_HCURR bit of RF_SETUP for a higher LNA gain, defaults to false.
+    ///
+    /// Only the nRF24L01 has this setting; the nRF24L01+ ignores it.
+    pub lna_gain: bool,
+    /// Events reflected on the IRQ pin, defaults to None (RX_DR only).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub interrupts: Option<Interrupts>,
 }
 
 /// Transmitter mode configuration
-#[derive(Debug, Default)]
+///
+/// Use `TXConfig::builder()` to have invalid values reported instead of capped.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
 pub struct TXConfig {
     /// data rate, defaults to `DataRate::R1Mbps`
     ///
@@ -216,6 +307,7 @@ pub struct TXConfig {
     /// F = 2400 + `channel` Mhz
     ///
     /// Any `channel` value above 125 is capped to 125.
+    /// See `Channel` to set it from a frequency.
     ///
     /// Both Transmitter and Receiver ends should use the same channel.
     pub channel: u8,
@@ -231,16 +323,56 @@ pub struct TXConfig {
     ///
     /// 0 <= `retry_delay` <= 15. Default is 0, recommended is > 1.
     /// Any value above 15 is capped to 15.
+    /// See `RetryDelay` to set it from a duration.
+    ///
+    /// The delay must leave enough time for the ACK to arrive, see `.min_retry_delay()`:
+    /// `.configure(...)` fails if it is too short. Ignored if `auto_retry_delay` is set.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "retry_delay_us", with = "config_file::retry_delay_micros")
+    )]
     pub retry_delay: u8, // [0, 15]
+    /// Use the smallest safe retry delay instead of `retry_delay`, defaults to false.
+    pub auto_retry_delay: bool,
+    /// Size of the largest ACK payload expected, in the range [0, 32], defaults to 0
+    /// (empty ACKs).
+    ///
+    /// Larger ACK payloads take longer to arrive and require a longer retry delay.
+    pub max_ack_payload: u8,
+    /// Set the LNA_HCURR bit of RF_SETUP for a higher LNA gain, defaults to false.
+    ///
+    /// Only the nRF24L01 has this setting; the nRF24L01+ ignores it.
+    pub lna_gain: bool,
     /// Destination address, should match an address on the receiver end.
     ///
     /// This is also the address on which ACK packets are received.
     /// The address is in little endian order: the first byte is the least significant one.
+    #[cfg_attr(feature = "serde", serde(with = "config_file::address"))]
     pub pipe0_address: [u8; 5],
+    /// Events reflected on the IRQ pin, defaults to None (TX_DS and MAX_RT).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub interrupts: Option<Interrupts>,
+}
+
+impl TXConfig {
+    /// The smallest retry delay leaving enough time for the ACK to arrive, given
+    /// the data rate and `max_ack_payload`.
+    pub fn min_retry_delay(&self) -> RetryDelay {
+        RetryDelay::minimum(
+            self.data_rate,
+            CRC_LENGTH,
+            ADDRESS_WIDTH,
+            self.max_ack_payload,
+        )
+    }
 }
 
 /// The Operating mode, either Receiver or Transmitter.
-#[derive(Debug)]
+///
+/// With the `serde` feature, it can be loaded from a file, see `OperatingMode::from_file`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "mode", rename_all = "lowercase"))]
 pub enum OperatingMode {
     /// Primary receiver
     RX(RXConfig),
@@ -248,6 +380,100 @@ pub enum OperatingMode {
     TX(TXConfig),
 }
 
+/// What to do with the TX queue when a packet reaches the maximum number of retries.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum MaxRetriesPolicy {
+    /// Keep all the packets, the failed one is sent again by the next transmission (default).
+    #[default]
+    Keep,
+    /// Drop the failed packet and keep the following ones.
+    DropFailed,
+    /// Drop all the packets.
+    FlushAll,
+}
+
+/// Outcome of a packet sent by `NRF24L01::send_detailed()`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum PacketOutcome {
+    /// Acknowledged after `retries` retries.
+    Delivered { retries: u8 },
+    /// Not acknowledged after the maximum number of retries, or within the time limit.
+    ///
+    /// Whether it remains in the TX queue depends on the `MaxRetriesPolicy`, which
+    /// is not applied on timeout.
+    Failed,
+    /// Not sent because a previous packet failed, still in the TX queue unless
+    /// the policy is `MaxRetriesPolicy::FlushAll`.
+    Unsent,
+}
+
+/// Progress of a transmission started by `NRF24L01::start_send()`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SendStatus {
+    /// Packets are still being sent.
+    Pending,
+    /// All the packets have been sent and acknowledged, with the total number of retries.
+    Sent { retries: u8 },
+    /// The maximum number of retries has been reached. The TX queue has been
+    /// handled according to the `MaxRetriesPolicy`.
+    Failed,
+}
+
+/// A packet received on one of the pipes.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Packet {
+    pipe: u8,
+    len: u8,
+    data: [u8; 32],
+}
+
+impl Packet {
+    /// The pipe the packet was received on, in the range [0, 5].
+    pub fn pipe(&self) -> u8 {
+        self.pipe
+    }
+
+    /// The payload of the packet.
+    pub fn payload(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+/// Counters of the anomalies met while reading the RX FIFO, see
+/// `NRF24L01::rx_anomalies()`.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct RxAnomalies {
+    /// Packets reported with an invalid payload width, 0 or above 32.
+    pub corrupt_packets: u32,
+    /// Times the RX FIFO has been flushed to recover.
+    pub flushes: u32,
+    /// Packets discarded by the flushes. This is a lower bound, as the device
+    /// doesn't tell how many packets the FIFO holds unless it is full.
+    pub discarded_packets: u32,
+}
+
+/// Report of a transmission by `NRF24L01::stream(...)`.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct StreamReport {
+    /// Number of packets delivered.
+    pub packets: usize,
+    /// Number of payload bytes delivered.
+    pub bytes: usize,
+    /// Duration of the transmission.
+    pub elapsed: Duration,
+}
+
+impl StreamReport {
+    /// Achieved throughput, in payload bytes per second.
+    pub fn throughput(&self) -> f64 {
+        if self.elapsed.as_secs_f64() > 0.0 {
+            self.bytes as f64 / self.elapsed.as_secs_f64()
+        } else {
+            0.0
+        }
+    }
+}
+
 type Command = u8;
 
 // Read register
@@ -275,6 +501,8 @@ const CONFIG: Register = 0;
 const EN_AA: Register = 0x01;
 // Enabled RX addresses, p 54
 const EN_RXADDR: Register = 0x02;
+// Address width, p 55
+const SETUP_AW: Register = 0x03;
 // Setup of automatic retransmission, p 55
 const SETUP_RETR: Register = 0x04;
 // Channel, p 55
@@ -304,6 +532,13 @@ const RX_ADDR_P4: Register = 0x0E;
 const RX_ADDR_P5: Register = 0x0F;
 // Destination address, p 57
 const TX_ADDR: Register = 0x10;
+// Static payload widths of pipes 0 to 5, p 58
+const RX_PW_P0: Register = 0x11;
+const RX_PW_P1: Register = 0x12;
+const RX_PW_P2: Register = 0x13;
+const RX_PW_P3: Register = 0x14;
+const RX_PW_P4: Register = 0x15;
+const RX_PW_P5: Register = 0x16;
 // FIFO status (RX & TX), p 58
 const FIFO_STATUS: Register = 0x17;
 // Enable dynamic payload length (requires EN_DPL and ENAA_PX), p 59
@@ -311,11 +546,132 @@ const DYNPD: Register = 0x1C;
 //  Feature register (content EN_DPL, EN_ACK_PAY...), p 59
 const FEATURE: Register = 0x1D;
 
+// We always use 2 bytes CRC and 5 bytes addresses
+const CRC_LENGTH: u8 = 2;
+const ADDRESS_WIDTH: u8 = 5;
+
+// Start up time from power down to standby (Tpd2stby), p 22
+const POWER_UP_DELAY: Duration = Duration::from_micros(1500);
+// Settling time from standby to RX or TX mode (Tstby2a), p 22
+const SETTLING_DELAY: Duration = Duration::from_micros(130);
+// Number of packets the RX FIFO can hold
+const RX_FIFO_DEPTH: usize = 3;
+// Polling intervals of the RX FIFO when no IRQ pin is available: polling starts fast
+// and slows down while nothing arrives
+const MIN_POLL_INTERVAL: Duration = Duration::from_micros(100);
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(5);
+// Time limit of `send()` for each queued packet, and of `stream()` without progress,
+// long enough for max retries
+const SEND_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Power-on values of the single byte registers, p 54-59
+const RESET_VALUES: [(Register, u8); 19] = [
+    (CONFIG, 0b0000_1000),
+    (EN_AA, 0b0011_1111),
+    (EN_RXADDR, 0b0000_0011),
+    (SETUP_AW, 0b0000_0011),
+    (SETUP_RETR, 0b0000_0011),
+    (RF_CH, 0b0000_0010),
+    (RF_SETUP, 0b0000_1110),
+    (RX_ADDR_P2, 0xC3),
+    (RX_ADDR_P3, 0xC4),
+    (RX_ADDR_P4, 0xC5),
+    (RX_ADDR_P5, 0xC6),
+    (RX_PW_P0, 0),
+    (RX_PW_P1, 0),
+    (RX_PW_P2, 0),
+    (RX_PW_P3, 0),
+    (RX_PW_P4, 0),
+    (RX_PW_P5, 0),
+    (DYNPD, 0),
+    (FEATURE, 0),
+];
+
+// Power-on values of the address registers, p 57
+const RESET_ADDRESSES: [(Register, [u8; 5]); 3] = [
+    (RX_ADDR_P0, [0xE7; 5]),
+    (RX_ADDR_P1, [0xC2; 5]),
+    (TX_ADDR, [0xE7; 5]),
+];
+
+// The content of a register, either a single byte or a full address
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum RegisterValue {
+    Byte(u8),
+    Address([u8; 5]),
+}
+
+impl fmt::Display for RegisterValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RegisterValue::Byte(byte) => write!(f, "{:#04X}", byte),
+            RegisterValue::Address(address) => write!(f, "{:#04X?}", address),
+        }
+    }
+}
+
+fn register_name(register: Register) -> &'static str {
+    match register {
+        CONFIG => "CONFIG",
+        EN_AA => "EN_AA",
+        EN_RXADDR => "EN_RXADDR",
+        SETUP_AW => "SETUP_AW",
+        SETUP_RETR => "SETUP_RETR",
+        RF_CH => "RF_CH",
+        RF_SETUP => "RF_SETUP",
+        RX_ADDR_P0 => "RX_ADDR_P0",
+        RX_ADDR_P1 => "RX_ADDR_P1",
+        RX_ADDR_P2 => "RX_ADDR_P2",
+        RX_ADDR_P3 => "RX_ADDR_P3",
+        RX_ADDR_P4 => "RX_ADDR_P4",
+        RX_ADDR_P5 => "RX_ADDR_P5",
+        TX_ADDR => "TX_ADDR",
+        DYNPD => "DYNPD",
+        FEATURE => "FEATURE",
+        _ => "register",
+    }
+}
+
 /// The driver
 pub struct NRF24L01 {
     ce: CEPin,
     spi: spidev::Spidev,
     base_config: u8,
+    // registers written by the last configuration
+    registers: Vec<(Register, RegisterValue)>,
+    // supervisor mode
+    supervision: Option<Duration>,
+    last_check: Instant,
+    recoveries: u32,
+    power_down_on_drop: bool,
+    max_retries_policy: MaxRetriesPolicy,
+    // None when powered down
+    powered_up_at: Option<Instant>,
+    // pipe 0 address in RX mode and destination in TX mode
+    rx_address: [u8; 5],
+    tx_address: [u8; 5],
+    regulatory_profile: RegulatoryProfile,
+    // external PA and LNA enable pins
+    tx_enable: Option<CEPin>,
+    rx_enable: Option<CEPin>,
+    irq: Option<IrqPin>,
+    rx_anomalies: RxAnomalies,
+    // payloads pushed to the TX FIFO in TX mode
+    tx_queue: VecDeque<Vec<u8>>,
+    // software queue feeding the TX FIFO, and its capacity if bounded
+    backlog: VecDeque<Vec<u8>>,
+    backlog_capacity: Option<usize>,
+    // transmission in progress, and retries of each packet it delivered
+    sending: bool,
+    delivered: Vec<u8>,
+}
+
+// Path of an external PA/LNA front end
+#[derive(Copy, Clone)]
+enum RfPath {
+    Idle,
+    Receive,
+    Transmit,
 }
 
 impl NRF24L01 {
@@ -340,7 +696,35 @@ impl NRF24L01 {
         Ok((response_buffer[0], response_buffer[1]))
     }
 
-    fn setup_rf(&self, rate: DataRate, level: PALevel) -> io::Result<()> {
+    fn read_address(&self, register: Register) -> io::Result<[u8; 5]> {
+        // For 5 bytes address registers only.
+        let mut response_buffer = [0u8; 6];
+        self.send_command(
+            &[R_REGISTER | register, 0, 0, 0, 0, 0],
+            &mut response_buffer,
+        )?;
+        let mut address = [0u8; 5];
+        address.copy_from_slice(&response_buffer[1..]);
+        Ok(address)
+    }
+
+    fn write_value(&self, register: Register, value: RegisterValue) -> io::Result<()> {
+        match value {
+            RegisterValue::Byte(byte) => self.write_register(register, byte),
+            RegisterValue::Address(address) => self.set_full_address(register, address),
+        }
+    }
+
+    fn read_value(&self, register: Register, like: RegisterValue) -> io::Result<RegisterValue> {
+        match like {
+            RegisterValue::Byte(_) => self
+                .read_register(register)
+                .map(|(_, byte)| RegisterValue::Byte(byte)),
+            RegisterValue::Address(_) => self.read_address(register).map(RegisterValue::Address),
+        }
+    }
+
+    fn setup_rf(rate: DataRate, level: PALevel, lna_gain: bool) -> u8 {
         let rate_bits: u8 = match rate {
             DataRate::R250Kbps => 0b0010_0000,
             DataRate::R1Mbps => 0,
@@ -352,17 +736,37 @@ impl NRF24L01 {
             PALevel::High => 0b0000_0100,
             PALevel::Max => 0b0000_0110,
         };
-        self.write_register(RF_SETUP, rate_bits | level_bits)
+        let lna_bit = u8::from(lna_gain);
+        rate_bits | level_bits | lna_bit
     }
 
-    fn set_channel(&self, channel: u8) -> io::Result<()> {
+    // Retransmission settings for a device configured in RX mode that switches to
+    // TX mode: the default 3 retries, with a delay long enough for a full ACK
+    // payload at the data rate set in `rf_setup`.
+    fn fallback_retransmission(rf_setup: u8) -> u8 {
+        let data_rate = if rf_setup & 0b0010_0000 != 0 {
+            DataRate::R250Kbps
+        } else if rf_setup & 0b0000_1000 != 0 {
+            DataRate::R2Mbps
+        } else {
+            DataRate::R1Mbps
+        };
+        let delay = RetryDelay::minimum(data_rate, CRC_LENGTH, ADDRESS_WIDTH, 32);
+        delay.value() << 4 | 0b0000_0011
+    }
+
+    fn channel_value(channel: u8) -> u8 {
         if channel < 126 {
-            self.write_register(RF_CH, channel)
+            channel
         } else {
-            self.write_register(RF_CH, 125)
+            125
         }
     }
 
+    fn set_channel(&self, channel: u8) -> io::Result<()> {
+        self.write_register(RF_CH, NRF24L01::channel_value(channel))
+    }
+
     fn set_full_address(&self, pipe: Register, address: [u8; 5]) -> io::Result<()> {
         let mut response_buffer = [0u8; 6];
         let mut command = [W_REGISTER | pipe, 0, 0, 0, 0, 0];
@@ -370,73 +774,114 @@ impl NRF24L01 {
         self.send_command(&command, &mut response_buffer)
     }
 
-    fn configure_receiver(&self, config: &RXConfig) -> io::Result<u8> {
+    // Compute the registers to write for `mode` and return the matching base config.
+    fn configure_mode(
+        mode: &OperatingMode,
+        registers: &mut Vec<(Register, RegisterValue)>,
+    ) -> io::Result<u8> {
+        // auto acknowlegement
+        registers.push((EN_AA, RegisterValue::Byte(0b0011_1111)));
+        // dynamic payload and payload with ACK
+        registers.push((DYNPD, RegisterValue::Byte(0b0011_1111)));
+        registers.push((FEATURE, RegisterValue::Byte(0b0000_0110)));
+        // Mode specific configuration
+        match *mode {
+            OperatingMode::RX(ref config) => Ok(NRF24L01::configure_receiver(config, registers)),
+            OperatingMode::TX(ref config) => NRF24L01::configure_transmitter(config, registers),
+        }
+    }
+
+    fn configure_receiver(config: &RXConfig, registers: &mut Vec<(Register, RegisterValue)>) -> u8 {
         // set data rate
         // set PA level
-        self.setup_rf(config.data_rate, config.pa_level)?;
+        let rf_setup = NRF24L01::setup_rf(config.data_rate, config.pa_level, config.lna_gain);
+        registers.push((RF_SETUP, RegisterValue::Byte(rf_setup)));
         // set channel
-        self.set_channel(config.channel)?;
+        let channel = NRF24L01::channel_value(config.channel);
+        registers.push((RF_CH, RegisterValue::Byte(channel)));
         // set Pipe 0 address
-        self.set_full_address(RX_ADDR_P0, config.pipe0_address)?;
+        registers.push((RX_ADDR_P0, RegisterValue::Address(config.pipe0_address)));
         let mut enabled = 1u8;
         // Pipe 1
         if let Some(address) = config.pipe1_address {
-            self.set_full_address(RX_ADDR_P1, address)?;
+            registers.push((RX_ADDR_P1, RegisterValue::Address(address)));
             enabled |= 0b0000_0010
         };
         // Pipe 2
         if let Some(lsb) = config.pipe2_addr_lsb {
-            self.write_register(RX_ADDR_P2, lsb)?;
+            registers.push((RX_ADDR_P2, RegisterValue::Byte(lsb)));
             enabled |= 0b0000_0100
         };
         // Pipe 3
         if let Some(lsb) = config.pipe3_addr_lsb {
-            self.write_register(RX_ADDR_P3, lsb)?;
+            registers.push((RX_ADDR_P3, RegisterValue::Byte(lsb)));
             enabled |= 0b0000_1000
         }
         // Pipe 4
         if let Some(lsb) = config.pipe4_addr_lsb {
-            self.write_register(RX_ADDR_P4, lsb)?;
+            registers.push((RX_ADDR_P4, RegisterValue::Byte(lsb)));
             enabled |= 0b0001_0000
         };
         // Pipe 5
         if let Some(lsb) = config.pipe5_addr_lsb {
-            self.write_register(RX_ADDR_P5, lsb)?;
+            registers.push((RX_ADDR_P5, RegisterValue::Byte(lsb)));
             enabled |= 0b0010_0000
         };
         // Enable configured pipes
-        self.write_register(EN_RXADDR, enabled)?;
-        // base config is 2 bytes for CRC and RX mode on
-        // only reflect RX_DR on the IRQ pin
-        Ok(0b0011_1101)
+        registers.push((EN_RXADDR, RegisterValue::Byte(enabled)));
+        // base config is 2 bytes for CRC and RX mode on
+        // only reflect RX_DR on the IRQ pin unless configured otherwise
+        let interrupts = config.interrupts.unwrap_or(Interrupts {
+            rx_dr: true,
+            ..Interrupts::NONE
+        });
+        interrupts.mask_bits() | 0b0000_1101
     }
 
-    fn configure_transmitter(&self, config: &TXConfig) -> io::Result<u8> {
+    fn configure_transmitter(
+        config: &TXConfig,
+        registers: &mut Vec<(Register, RegisterValue)>,
+    ) -> io::Result<u8> {
         // set data rate
         // set PA level
-        self.setup_rf(config.data_rate, config.pa_level)?;
+        let rf_setup = NRF24L01::setup_rf(config.data_rate, config.pa_level, config.lna_gain);
+        registers.push((RF_SETUP, RegisterValue::Byte(rf_setup)));
         // set channel
-        self.set_channel(config.channel)?;
+        let channel = NRF24L01::channel_value(config.channel);
+        registers.push((RF_CH, RegisterValue::Byte(channel)));
         // set destination and Pipe 0 address
-        self.set_full_address(RX_ADDR_P0, config.pipe0_address)?;
-        self.set_full_address(TX_ADDR, config.pipe0_address)?;
+        registers.push((RX_ADDR_P0, RegisterValue::Address(config.pipe0_address)));
+        registers.push((TX_ADDR, RegisterValue::Address(config.pipe0_address)));
         // disable other pipes
-        self.write_register(EN_RXADDR, 1u8)?;
+        registers.push((EN_RXADDR, RegisterValue::Byte(1u8)));
         // retransmission settings
         let retry_bits: u8 = if config.max_retries < 16 {
             config.max_retries
         } else {
             15
         };
-        let retry_delay_bits: u8 = if config.retry_delay < 16 {
-            config.retry_delay << 4
+        let min_retry_delay = config.min_retry_delay().value();
+        let retry_delay: u8 = if config.auto_retry_delay {
+            min_retry_delay
+        } else if config.retry_delay < 16 {
+            config.retry_delay
         } else {
-            0xF0
+            15
         };
-        self.write_register(SETUP_RETR, retry_delay_bits | retry_bits)?;
-        // base config is 2 bytes for CRC and TX mode on
-        // only reflect TX_DS and MAX_RT on the IRQ pin
-        Ok(0b0100_1100)
+        if retry_delay < min_retry_delay {
+            return Err(config.retry_delay_error());
+        }
+        registers.push((
+            SETUP_RETR,
+            RegisterValue::Byte(retry_delay << 4 | retry_bits),
+        ));
+        // base config is 2 bytes for CRC and TX mode on
+        // only reflect TX_DS and MAX_RT on the IRQ pin unless configured otherwise
+        let interrupts = config.interrupts.unwrap_or(Interrupts {
+            rx_dr: false,
+            ..Interrupts::ALL
+        });
+        Ok(interrupts.mask_bits() | 0b0000_1100)
     }
 
     // Public API
@@ -465,35 +910,223 @@ impl NRF24L01 {
             ce,
             spi,
             base_config: 0b0000_1101,
+            registers: Vec::new(),
+            supervision: None,
+            last_check: Instant::now(),
+            recoveries: 0,
+            power_down_on_drop: true,
+            max_retries_policy: MaxRetriesPolicy::default(),
+            powered_up_at: None,
+            rx_address: [0u8; 5],
+            tx_address: [0u8; 5],
+            regulatory_profile: RegulatoryProfile::default(),
+            tx_enable: None,
+            rx_enable: None,
+            irq: None,
+            rx_anomalies: RxAnomalies::default(),
+            tx_queue: VecDeque::new(),
+            backlog: VecDeque::new(),
+            backlog_capacity: None,
+            sending: false,
+            delivered: Vec::new(),
         })
     }
 
+    /// Reset the device to its power-on state.
+    ///
+    /// All registers are restored to their datasheet default values, both FIFOs
+    /// are flushed and the pending interrupt flags are cleared, whatever a previous
+    /// process left behind. The device is powered down afterwards.
+    ///
+    /// The software queue (see `.enqueue(...)`) is kept, `.flush_output()` clears it.
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.ce.down()?;
+        self.sending = false;
+        self.powered_up_at = None;
+        self.flush_input()?;
+        self.flush_fifo()?;
+        // clear RX_DR, TX_DS and MAX_RT
+        self.write_register(STATUS, 0b0111_0000)?;
+        for &(register, value) in RESET_VALUES.iter() {
+            self.write_register(register, value)?;
+        }
+        for &(register, address) in RESET_ADDRESSES.iter() {
+            self.set_full_address(register, address)?;
+        }
+        Ok(())
+    }
+
     /// Configure the device as Primary Receiver (PRX) or Primary Transmitter (PTX),
     /// set all its properties for proper operation and power it up.
     ///
+    /// The device is `reset()` first, so that no setting left by a previous
+    /// process survives.
+    ///
     /// The device remain in standby until `self.listen()` (RX mode)
     /// or `self.send()` (TX mode) is called.
     ///
     /// All commands work when the device is in standby (recommended) as well as
     /// active state.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors, a custom io::ErrorKind::InvalidInput if the configuration
+    /// is not allowed by the regulatory profile (see `.set_regulatory_profile(...)`) or
+    /// if the retry delay is too short, and a custom io::ErrorKind::InvalidData if
+    /// the configuration could not be read back.
     pub fn configure(&mut self, mode: &OperatingMode) -> io::Result<()> {
-        self.ce.down()?;
-        // auto acknowlegement
-        self.write_register(EN_AA, 0b0011_1111)?;
-        // dynamic payload and payload with ACK
-        self.write_register(DYNPD, 0b0011_1111)?;
-        self.write_register(FEATURE, 0b0000_0110)?;
+        self.regulatory_profile.check(mode)?;
+        let mut registers = Vec::new();
+        self.base_config = NRF24L01::configure_mode(mode, &mut registers)?;
+        self.registers = registers;
+        let pipe0_address = match *mode {
+            OperatingMode::RX(ref config) => config.pipe0_address,
+            OperatingMode::TX(ref config) => config.pipe0_address,
+        };
+        self.rx_address = pipe0_address;
+        self.tx_address = pipe0_address;
+        self.apply_configuration()
+    }
 
-        // Mode specific configuration
-        match *mode {
-            OperatingMode::RX(ref config) => self.configure_receiver(config),
-            OperatingMode::TX(ref config) => self.configure_transmitter(config),
+    // Write a register and record it in the current configuration.
+    fn set_register(&mut self, register: Register, value: RegisterValue) -> io::Result<()> {
+        self.write_value(register, value)?;
+        match self.registers.iter_mut().find(|entry| entry.0 == register) {
+            Some(entry) => entry.1 = value,
+            None => self.registers.push((register, value)),
         }
-        .and_then(|base_config| {
-            // Go!
-            self.base_config = base_config;
-            self.power_up()
-        })
+        Ok(())
+    }
+
+    // Write the last configuration from scratch and power the device up.
+    fn apply_configuration(&mut self) -> io::Result<()> {
+        self.reset()?;
+        for &(register, value) in self.registers.iter() {
+            self.write_value(register, value)?;
+        }
+        // Go!
+        self.power_up()?;
+        self.verify_configuration()
+    }
+
+    // Compare the registers with the last configuration and describe the differences.
+    fn configuration_mismatches(&self) -> io::Result<Vec<String>> {
+        let mut mismatches = Vec::new();
+        // PWR_UP reflects the power state, not the configuration
+        let (_, config) = self.read_register(CONFIG)?;
+        if config & !0b0000_0010 != self.base_config {
+            mismatches.push(format!(
+                "CONFIG (0x00): wrote {:#04X}, read {:#04X}",
+                self.base_config, config
+            ));
+        }
+        for &(register, expected) in self.registers.iter() {
+            let actual = self.read_value(register, expected)?;
+            if actual != expected {
+                mismatches.push(format!(
+                    "{} ({:#04X}): wrote {}, read {}",
+                    register_name(register),
+                    register,
+                    expected,
+                    actual
+                ));
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Read back every register set by the last `.configure(...)` and compare it with
+    /// the intended value.
+    ///
+    /// `configure` already calls it, but you may call it again at any time to
+    /// check the link with the device is still sound.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors as well as a custom io::ErrorKind::InvalidData listing
+    /// all the mismatching registers. Such mismatches are usually caused by
+    /// loose wires or a wrong SPI mode.
+    pub fn verify_configuration(&self) -> io::Result<()> {
+        let mismatches = self.configuration_mismatches()?;
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Configuration readback mismatch: {}", mismatches.join("; ")),
+            ))
+        }
+    }
+
+    /// Restrict the channels and power levels the device may use.
+    ///
+    /// Defaults to `RegulatoryProfile::UNRESTRICTED`. The profile applies to the next
+    /// calls to `.configure(...)` and `.scan(...)`.
+    pub fn set_regulatory_profile(&mut self, profile: RegulatoryProfile) {
+        self.regulatory_profile = profile;
+    }
+
+    /// Check the device still holds the last configuration and re-apply it if not.
+    ///
+    /// A brownout, common with PA+LNA modules on a weak 3.3 V rail, silently resets
+    /// the device to its power-on defaults. This function detects it and restores
+    /// the configuration, as well as the listening state in RX mode.
+    ///
+    /// Return `true` if the configuration had to be re-applied, `false` if the
+    /// device was fine or has never been configured.
+    pub fn check_and_recover(&mut self) -> io::Result<bool> {
+        if self.registers.is_empty() || self.configuration_mismatches()?.is_empty() {
+            return Ok(false);
+        }
+        self.ce.save_state();
+        self.apply_configuration()?;
+        self.ce.restore_state()?;
+        // the TX FIFO has been flushed, refill it from the software queue
+        self.fill_fifo()?;
+        self.recoveries += 1;
+        Ok(true)
+    }
+
+    /// Enable or disable (`None`) the supervisor mode.
+    ///
+    /// When enabled, `.listen()`, `.read_all(...)` and `.send()` call
+    /// `.check_and_recover()` if at least `period` has elapsed since the last check,
+    /// and so does the thread of a `BackgroundReceiver` or `RadioHandle` owning the
+    /// device.
+    /// Use `.recoveries()` to know how many times the configuration has been restored.
+    pub fn set_supervision(&mut self, period: Option<Duration>) {
+        self.supervision = period;
+        self.last_check = Instant::now();
+    }
+
+    /// Number of times the configuration has been re-applied by `.check_and_recover()`.
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+
+    // Check the configuration if the supervisor mode is on and a check is due.
+    pub(crate) fn supervise(&mut self) -> io::Result<bool> {
+        let mut recovered = false;
+        if let Some(period) = self.supervision {
+            if self.last_check.elapsed() >= period {
+                recovered = self.check_and_recover()?;
+                self.last_check = Instant::now();
+            }
+        }
+        Ok(recovered)
+    }
+
+    // Supervise before sending: restoring the configuration flushes the packets
+    // in the TX FIFO, which must not be reported as sent.
+    fn supervise_send(&mut self) -> io::Result<()> {
+        let queued = !self.tx_queue.is_empty();
+        if self.supervise()? && queued {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "Configuration restored, queued packets lost!",
+            ));
+        }
+        Ok(())
     }
 
     /// Scan all channel.
@@ -502,6 +1135,8 @@ impl NRF24L01 {
     /// milliseconds for a carrier on each. The number of time a carrier has been
     /// detected is placed in the ``channel_table`` array at the corresponding index.
     ///
+    /// Channels not allowed by the regulatory profile are skipped.
+    ///
     /// You don't need to call ``.configure`` before using this function, but
     /// you must do it after if you plan to use the device as a receiver or
     /// transmitter.
@@ -511,11 +1146,15 @@ impl NRF24L01 {
         wait_ms: u32,
         channel_table: &mut [u32; 126],
     ) -> io::Result<()> {
+        self.power_up()?;
         self.write_register(EN_AA, 0u8)?;
         for _ in 0..nb_iter {
             for channel in 0..126 {
+                if !self.regulatory_profile.allows_channel(channel) {
+                    continue;
+                }
                 self.set_channel(channel)?;
-                self.listen()?;
+                self.start_listening()?;
                 sleep(Duration::from_millis(wait_ms as u64));
                 self.standby()?;
                 let (_, rpd) = self.read_register(RPD)?;
@@ -527,100 +1166,596 @@ impl NRF24L01 {
         Ok(())
     }
 
-    pub fn is_receiver(&self) -> bool {
-        self.base_config & 1u8 == 1u8
+    /// Set the GPIO pin wired to the IRQ pin of the device, or `None` if not wired (default).
+    ///
+    /// When set, the functions waiting for incoming packets wait for the IRQ instead
+    /// of polling the device over SPI, provided RX_DR is reflected on the IRQ pin
+    /// (see `Interrupts`). They go back to polling when the IRQ is asserted without
+    /// incoming data, by another event or by another device sharing the line.
+    ///
+    /// # Errors
+    ///
+    /// System IO errors if the pin can't be set up.
+    pub fn set_irq_pin(&mut self, irq: Option<u64>) -> io::Result<()> {
+        if let Some(mut irq) = self.irq.take() {
+            irq.release()?;
+        }
+        self.irq = irq.map(IrqPin::new).transpose()?;
+        Ok(())
     }
 
-    /// Power down the device.
-    ///
-    /// The power consumption is minimum in this mode, and the device ceases all operation.
-    /// It only accepts configuration commands.
-    pub fn power_down(&mut self) -> io::Result<()> {
-        self.ce.down()?;
-        self.write_register(CONFIG, self.base_config)
+    // Wait for incoming data for at most `timeout`, on the IRQ if available, or
+    // polling at increasing intervals up to `max_interval`.
+    // Return whether data is available.
+    pub(crate) fn wait_for_data(
+        &mut self,
+        timeout: Duration,
+        max_interval: Duration,
+    ) -> io::Result<bool> {
+        let started = Instant::now();
+        // MASK_RX_DR cleared: incoming data asserts the IRQ
+        let rx_dr_irq = self.base_config & 0b0100_0000 == 0;
+        if let (Some(irq), true) = (self.irq.as_mut(), rx_dr_irq) {
+            irq.wait(timeout)?;
+            // otherwise the line is held low by another event or device: poll
+            // for the rest of the time instead of waking up again right away
+            if self.data_available()? {
+                return Ok(true);
+            }
+        }
+        let mut interval = MIN_POLL_INTERVAL.min(max_interval);
+        loop {
+            if self.data_available()? {
+                return Ok(true);
+            }
+            let elapsed = started.elapsed();
+            if elapsed >= timeout {
+                return Ok(false);
+            }
+            sleep(interval.min(timeout - elapsed));
+            interval = (interval * 2).min(max_interval);
+        }
     }
 
-    /// Power the device up for full operation.
-    pub fn power_up(&self) -> io::Result<()> {
-        self.write_register(CONFIG, self.base_config | 0b0000_0010)
+    /// Wait for the next packet for at most `timeout`, and return it.
+    ///
+    /// In RX mode, the device should be listening. In TX mode, this returns the
+    /// next ACK payload.
+    ///
+    /// The function waits on the IRQ pin if set (see `.set_irq_pin(...)`), and polls
+    /// the device otherwise, less and less often while no data arrives, up to every
+    /// 5 ms. The RX FIFO may then overflow during a burst following a quiet period:
+    /// use the IRQ pin or a `BackgroundReceiver` to keep up with bursts.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors and a custom io::ErrorKind::TimedOut if no packet
+    /// arrives in time.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Packet> {
+        self.supervise()?;
+        let started = Instant::now();
+        loop {
+            self.clear_rx_dr()?;
+            if let Some(packet) = self.read_packet()? {
+                return Ok(packet);
+            }
+            let elapsed = started.elapsed();
+            if elapsed >= timeout {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "No packet received in time!",
+                ));
+            }
+            // waking up without data, on a shared IRQ line for instance, is not a timeout
+            self.wait_for_data(timeout - elapsed, MAX_POLL_INTERVAL)?;
+        }
     }
 
-    /// Put the device in standby (RX Mode)
+    /// Wait for the next packet, and return it.
     ///
-    /// Only used in RX mode to suspend active listening.
-    /// In TX mode, standby is the default state when not sending data.
-    pub fn standby(&mut self) -> io::Result<()> {
-        self.ce.down()?; // always returnss without error.
+    /// Same as `.recv_timeout(...)` without time limit.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors.
+    pub fn recv(&mut self) -> io::Result<Packet> {
+        loop {
+            match self.recv_timeout(Duration::from_secs(1)) {
+                Err(ref err) if err.kind() == io::ErrorKind::TimedOut => {}
+                result => return result,
+            }
+        }
+    }
+
+    // Read the packet at the head of the RX FIFO, if any.
+    fn read_packet(&mut self) -> io::Result<Option<Packet>> {
+        let mut packet = Packet {
+            pipe: 0,
+            len: 0,
+            data: [0u8; 32],
+        };
+        Ok(self.read_payload(&mut packet.data)?.map(|(pipe, len)| {
+            packet.pipe = pipe;
+            packet.len = len as u8;
+            packet
+        }))
+    }
+
+    // Clear RX_DR before reading the RX FIFO, so that the packets arriving meanwhile
+    // set it again.
+    fn clear_rx_dr(&self) -> io::Result<()> {
+        self.write_register(STATUS, 0b0100_0000)
+    }
+
+    // Read the payload at the head of the RX FIFO into `buffer`, if any.
+    // Return the pipe number and the payload length.
+    fn read_payload(&mut self, buffer: &mut [u8]) -> io::Result<Option<(u8, usize)>> {
+        let (status, fifo_status) = self.read_register(FIFO_STATUS)?;
+        if fifo_status & 1 != 0 {
+            // RX FIFO empty
+            return Ok(None);
+        }
+        let mut pl_wd: [u8; 2] = [0, 0];
+        self.send_command(&[R_RX_PL_WID, 0], &mut pl_wd)?;
+        let width = match NRF24L01::payload_width(pl_wd[1]) {
+            Some(width) => width,
+            None => {
+                // corrupt packet, the FIFO must be flushed, p 51
+                self.rx_anomalies.corrupt_packets += 1;
+                self.discard_input()?;
+                return Ok(None);
+            }
+        };
+        if width > buffer.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Buffer too small for a {} bytes packet!", width),
+            ));
+        }
+        let mut receive_buffer = [0u8; 33];
+        let out_buffer = [R_RX_PAYLOAD; 33];
+        self.send_command(&out_buffer[..=width], &mut receive_buffer[..=width])?;
+        buffer[..width].copy_from_slice(&receive_buffer[1..=width]);
+        // RX_P_NO
+        Ok(Some(((status >> 1) & 0b111, width)))
+    }
+
+    // Payload width from the R_RX_PL_WID response, None if it is invalid.
+    fn payload_width(pl_wid: u8) -> Option<usize> {
+        match pl_wid {
+            1..=32 => Some(pl_wid as usize),
+            _ => None,
+        }
+    }
+
+    // Flush the RX FIFO, whose content can't be trusted.
+    fn discard_input(&mut self) -> io::Result<()> {
+        let (_, fifo_status) = self.read_register(FIFO_STATUS)?;
+        // RX_FULL
+        let discarded = if fifo_status & 0b0000_0010 != 0 { 3 } else { 1 };
+        self.flush_input()?;
+        self.rx_anomalies.flushes += 1;
+        self.rx_anomalies.discarded_packets += discarded;
         Ok(())
     }
 
-    /// (RX mode only) Wake up and activate receiver.
-    ///
-    /// In RX mode, call this function after a `.configure(...)`, `.standby()` or `power_up()` to
-    /// accept incoming packets.
-    pub fn listen(&mut self) -> io::Result<()> {
-        if self.is_receiver() {
-            self.ce.up()?;
+    // In standby, the RX FIFO must be empty once RX_FIFO_DEPTH packets have been read.
+    // Otherwise, its status can't be trusted.
+    fn check_input_drained(&mut self) -> io::Result<()> {
+        if self.data_available()? {
+            self.discard_input()?;
         }
         Ok(())
     }
 
-    /// Is there any incoming data to read?
+    /// Counters of the anomalies met while reading the RX FIFO.
     ///
-    /// Works in both RX and TX modes. In TX mode, this function returns true if
-    /// a ACK payload has been received.
-    pub fn data_available(&self) -> io::Result<bool> {
-        self.read_register(FIFO_STATUS)
-            .and_then(|(_, fifo_status)| Ok(fifo_status.trailing_zeros() >= 1))
+    /// When the device reports an invalid payload width, a known silicon issue, or more
+    /// packets than the RX FIFO can hold, the FIFO is flushed as recommended by the
+    /// datasheet and the anomaly is counted here.
+    pub fn rx_anomalies(&self) -> RxAnomalies {
+        self.rx_anomalies
     }
 
-    /// Read data from the receiver queue, one packet at a time.
-    ///
-    /// The `process_packet` callback is fired for each packet, and is
-    /// passed a slice into the packet data as argument.
-    ///
-    /// ``read_all`` returns the number of messages read.
-    ///
-    /// **Note**: this function puts the device in standby mode during
-    /// the processing of the queue and restores operations when it returns *successfully*.
-    /// So the `process_packet` callback should better return quickly.
-    pub fn read_all<F>(&mut self, mut process_packet: F) -> io::Result<u8>
+    // Stop listening while the RX FIFO is read, see `.resume_listening()`.
+    fn suspend_listening(&mut self) -> io::Result<()> {
+        self.ce.save_state();
+        self.ce.down()
+    }
+
+    // Clear RX_DR and restore the CE state saved by `.suspend_listening()`.
+    fn resume_listening(&mut self) -> io::Result<()> {
+        self.write_register(STATUS, 0b0100_0000)?;
+        self.ce.restore_state()
+    }
+
+    // Read the packets in the RX FIFO without leaving the current mode, and clear
+    // RX_DR. At most a FIFO's worth is read, so that a continuous flow of packets
+    // can't keep the caller here: return whether more packets may be waiting.
+    pub(crate) fn drain_packets<F>(&mut self, mut process_packet: F) -> io::Result<bool>
     where
-        F: FnMut(&[u8]) -> (),
+        F: FnMut(Packet),
     {
-        // communication buffers
-        let mut pl_wd: [u8; 2] = [0, 0]; // for packet width
-        let mut receive_buffer = [0u8; 33]; // for packet
-        let out_buffer = [R_RX_PAYLOAD; 33]; // for command
-                                             // message counter
-        let mut count = 0u8;
-        // save CE state
-        self.ce.save_state();
-        // Standby
-        self.ce.down()?;
-        // process queue
-        while self.data_available()? {
-            self.send_command(&[R_RX_PL_WID, 0], &mut pl_wd)?;
-            let width = pl_wd[1] as usize;
-            if width != 0 {
-                // can it be false?
-                let ubound = width + 1;
-                self.send_command(&out_buffer[..ubound], &mut receive_buffer[..ubound])?;
-                process_packet(&receive_buffer[1..ubound]);
-                count += 1;
+        self.clear_rx_dr()?;
+        for _ in 0..RX_FIFO_DEPTH {
+            match self.read_packet()? {
+                Some(packet) => process_packet(packet),
+                None => return Ok(false),
             }
         }
-        // Clear interrupt
-        self.write_register(STATUS, 0b0100_0000)?;
-        // Restore previous CE state
-        self.ce.restore_state()?;
-        Ok(count)
+        Ok(true)
     }
 
-    /// Queue (FIFO) data to be sent, one packet at a time.
+    pub fn is_receiver(&self) -> bool {
+        self.base_config & 1u8 == 1u8
+    }
+
+    /// Power down the device.
     ///
-    /// In TX mode, `pipe_num` is ignored. In RX mode, this function queues an ACK payload
-    /// for the next message to arrive on `pipe_num`. if `pipe_num` is bigger than 5,
+    /// The power consumption is minimum in this mode, and the device ceases all operation.
+    /// It only accepts configuration commands.
+    pub fn power_down(&mut self) -> io::Result<()> {
+        self.ce.down()?;
+        self.sending = false;
+        self.switch_rf_path(RfPath::Idle)?;
+        self.write_register(CONFIG, self.base_config)?;
+        self.powered_up_at = None;
+        Ok(())
+    }
+
+    /// Choose whether dropping the driver powers the device down (default).
+    ///
+    /// By default, when the driver is dropped, the device is powered down with CE low
+    /// and the CE GPIO is released. Pass `false` if you intentionally hand
+    /// the radio over to another process: the device and the CE pin are then left as is.
+    pub fn set_power_down_on_drop(&mut self, enabled: bool) {
+        self.power_down_on_drop = enabled;
+    }
+
+    /// Choose what happens to the TX queue when a packet reaches the maximum
+    /// number of retries, defaults to `MaxRetriesPolicy::Keep`.
+    ///
+    /// `MaxRetriesPolicy::DropFailed` can only restore the packets pushed in TX mode.
+    pub fn set_max_retries_policy(&mut self, policy: MaxRetriesPolicy) {
+        self.max_retries_policy = policy;
+    }
+
+    /// Set the GPIO pins enabling the external PA (`tx_enable`) and LNA (`rx_enable`)
+    /// of PA+LNA modules that need them, or `None` if not wired.
+    ///
+    /// The pins are driven high only while needed: `tx_enable` during `.send()`,
+    /// `rx_enable` from `.listen()` to `.standby()`. Both are low otherwise.
+    ///
+    /// # Errors
+    ///
+    /// System IO errors if a pin can't be set up.
+    pub fn set_pa_lna_pins(
+        &mut self,
+        tx_enable: Option<u64>,
+        rx_enable: Option<u64>,
+    ) -> io::Result<()> {
+        self.release_pa_lna_pins();
+        self.tx_enable = tx_enable.map(CEPin::new).transpose()?;
+        self.rx_enable = rx_enable.map(CEPin::new).transpose()?;
+        Ok(())
+    }
+
+    fn release_pa_lna_pins(&mut self) {
+        for pin in self.tx_enable.iter_mut().chain(self.rx_enable.iter_mut()) {
+            // errors can't be reported from here
+            let _ = pin.down();
+            let _ = pin.release();
+        }
+        self.tx_enable = None;
+        self.rx_enable = None;
+    }
+
+    fn switch_rf_path(&mut self, path: RfPath) -> io::Result<()> {
+        // always disable before enabling, never both
+        let (tx, rx) = match path {
+            RfPath::Idle => (false, false),
+            RfPath::Receive => (false, true),
+            RfPath::Transmit => (true, false),
+        };
+        for (pin, enabled) in [(&mut self.tx_enable, tx), (&mut self.rx_enable, rx)] {
+            if let (Some(pin), false) = (pin.as_mut(), enabled) {
+                pin.down()?;
+            }
+        }
+        for (pin, enabled) in [(&mut self.tx_enable, tx), (&mut self.rx_enable, rx)] {
+            if let (Some(pin), true) = (pin.as_mut(), enabled) {
+                pin.up()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Power the device up for full operation.
+    ///
+    /// The device needs 1.5 ms to start up. This function returns immediately, but
+    /// `.listen()` and `.send()` wait for the remaining start up time if needed.
+    pub fn power_up(&mut self) -> io::Result<()> {
+        self.write_register(CONFIG, self.base_config | 0b0000_0010)?;
+        if self.powered_up_at.is_none() {
+            self.powered_up_at = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Is the device powered up?
+    pub fn is_powered_up(&self) -> bool {
+        self.powered_up_at.is_some()
+    }
+
+    // Wait until the device has reached standby after power up.
+    fn wait_for_standby(&self) -> io::Result<()> {
+        match self.powered_up_at {
+            Some(powered_up_at) => {
+                let elapsed = powered_up_at.elapsed();
+                if elapsed < POWER_UP_DELAY {
+                    sleep(POWER_UP_DELAY - elapsed);
+                }
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "Device is powered down!",
+            )),
+        }
+    }
+
+    /// Switch to TX mode (Primary Transmitter) without reconfiguring the device.
+    ///
+    /// Only PRIM_RX and the pipe 0 address are changed: pipe 0 is set to the
+    /// destination address so that ACKs are received. All other settings are kept,
+    /// which makes this function suitable for fast half-duplex protocols.
+    ///
+    /// The destination is the last one given to `.set_destination(...)`, or the
+    /// configured `pipe0_address`. If the device has been configured in RX mode,
+    /// it retransmits 3 times, waiting long enough between tries for a 32 bytes ACK
+    /// payload at the configured data rate.
+    ///
+    /// **Note**: payloads pushed in RX mode (ACK payloads) still in the output queue
+    /// are sent as regular packets. Call `.flush_output()` if you don't want that.
+    pub fn enter_tx(&mut self) -> io::Result<()> {
+        self.ce.down()?;
+        if self.is_receiver() {
+            self.tx_queue.clear();
+            // leave RX mode first, so that `.enter_rx()` restores pipe 0 if this fails
+            self.base_config &= !0b0000_0001;
+            self.write_config()?;
+            let address = self.tx_address;
+            self.set_register(RX_ADDR_P0, RegisterValue::Address(address))?;
+            self.set_register(TX_ADDR, RegisterValue::Address(address))?;
+            if !self
+                .registers
+                .iter()
+                .any(|&(register, _)| register == SETUP_RETR)
+            {
+                let (_, rf_setup) = self.read_register(RF_SETUP)?;
+                let setup_retr = NRF24L01::fallback_retransmission(rf_setup);
+                self.set_register(SETUP_RETR, RegisterValue::Byte(setup_retr))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Switch to RX mode (Primary Receiver) without reconfiguring the device.
+    ///
+    /// Only PRIM_RX and the pipe 0 address are changed: pipe 0 is set back to its
+    /// receiving address. All other settings are kept.
+    ///
+    /// The device is left in standby: call `.listen()` to accept incoming packets.
+    pub fn enter_rx(&mut self) -> io::Result<()> {
+        self.ce.down()?;
+        if !self.is_receiver() {
+            self.tx_queue.clear();
+            let address = self.rx_address;
+            self.set_register(RX_ADDR_P0, RegisterValue::Address(address))?;
+            self.base_config |= 0b0000_0001;
+            self.write_config()?;
+        }
+        Ok(())
+    }
+
+    /// Set the destination address of the next packets.
+    ///
+    /// In TX mode, TX_ADDR and the pipe 0 address (on which ACKs are received) are
+    /// rewritten, but only if the destination changes. In RX mode, the destination
+    /// is recorded and applied by the next `.enter_tx()`; the pipe 0 receiving
+    /// address is left untouched.
+    ///
+    /// **Note**: the new destination also applies to the packets already queued.
+    pub fn set_destination(&mut self, address: [u8; 5]) -> io::Result<()> {
+        if address != self.tx_address {
+            self.tx_address = address;
+            if !self.is_receiver() {
+                self.set_register(RX_ADDR_P0, RegisterValue::Address(address))?;
+                self.set_register(TX_ADDR, RegisterValue::Address(address))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// [TX mode only] Send `data` to the given destination `address`.
+    ///
+    /// This is a shortcut for `.set_destination(address)`, `.push(0, data)` and
+    /// `.send()`. The pipe 0 receiving address is restored by `.enter_rx()` when
+    /// the device goes back to listening.
+    ///
+    /// # Errors
+    ///
+    /// Same as `.push(...)` and `.send()`, and a custom io::ErrorKind::InvalidInput
+    /// in RX mode.
+    pub fn send_to(&mut self, address: [u8; 5], data: &[u8]) -> io::Result<u8> {
+        if self.is_receiver() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot send in RX mode, call enter_tx() first!",
+            ));
+        }
+        self.set_destination(address)?;
+        self.push(0, data)?;
+        self.send()
+    }
+
+    // Write the base config, keeping the current power state.
+    fn write_config(&self) -> io::Result<()> {
+        if self.is_powered_up() {
+            self.write_register(CONFIG, self.base_config | 0b0000_0010)
+        } else {
+            self.write_register(CONFIG, self.base_config)
+        }
+    }
+
+    /// Put the device in standby (RX Mode)
+    ///
+    /// Only used in RX mode to suspend active listening.
+    /// In TX mode, standby is the default state when not sending data.
+    pub fn standby(&mut self) -> io::Result<()> {
+        self.ce.down()?; // always returnss without error.
+        self.switch_rf_path(RfPath::Idle)
+    }
+
+    /// (RX mode only) Wake up and activate receiver.
+    ///
+    /// In RX mode, call this function after a `.configure(...)`, `.standby()` or `power_up()` to
+    /// accept incoming packets.
+    ///
+    /// The function returns once the receiver has settled and is actually listening.
+    ///
+    /// # Errors
+    ///
+    /// Return a custom io::ErrorKind::NotConnected if the device is powered down.
+    pub fn listen(&mut self) -> io::Result<()> {
+        self.supervise()?;
+        if self.is_receiver() {
+            self.start_listening()?;
+        }
+        Ok(())
+    }
+
+    fn start_listening(&mut self) -> io::Result<()> {
+        self.wait_for_standby()?;
+        self.switch_rf_path(RfPath::Receive)?;
+        self.ce.up()?;
+        sleep(SETTLING_DELAY);
+        Ok(())
+    }
+
+    /// Is there any incoming data to read?
+    ///
+    /// Works in both RX and TX modes. In TX mode, this function returns true if
+    /// a ACK payload has been received.
+    pub fn data_available(&self) -> io::Result<bool> {
+        self.read_register(FIFO_STATUS)
+            .map(|(_, fifo_status)| fifo_status.trailing_zeros() >= 1)
+    }
+
+    // Is there any packet in the TX FIFO? In RX mode, ACK payloads.
+    pub(crate) fn output_pending(&self) -> io::Result<bool> {
+        self.read_register(FIFO_STATUS)
+            .map(|(_, fifo_status)| fifo_status & 0x10 == 0)
+    }
+
+    /// Read data from the receiver queue, one packet at a time.
+    ///
+    /// The `process_packet` callback is fired for each packet, and is
+    /// passed a slice into the packet data as argument.
+    ///
+    /// ``read_all`` returns the number of messages read.
+    /// Corrupt packets are discarded, see `.rx_anomalies()`.
+    ///
+    /// **Note**: this function puts the device in standby mode during
+    /// the processing of the queue and restores operations when it returns *successfully*.
+    /// So the `process_packet` callback should better return quickly.
+    pub fn read_all<F>(&mut self, mut process_packet: F) -> io::Result<u8>
+    where
+        F: FnMut(&[u8]),
+    {
+        self.supervise()?;
+        let mut buffer = [0u8; 32];
+        // message counter
+        let mut count = 0u8;
+        // Standby, no packet can arrive meanwhile
+        self.suspend_listening()?;
+        // process queue
+        for _ in 0..RX_FIFO_DEPTH {
+            match self.read_payload(&mut buffer)? {
+                Some((_, width)) => {
+                    process_packet(&buffer[..width]);
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        self.check_input_drained()?;
+        // Clear interrupt and restore previous CE state
+        self.resume_listening()?;
+        Ok(count)
+    }
+
+    /// Read the next packet from the receiver queue into `buffer`, without allocating.
+    ///
+    /// Return the pipe number the packet was received on and its length, or `None`
+    /// if the queue is empty. Contrary to `.read_all(...)`, the device keeps listening.
+    ///
+    /// ```rust,no_run
+    /// # use nrf24l01::NRF24L01;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let mut device = NRF24L01::new(25, 0)?;
+    /// let mut buffer = [0u8; 32];
+    /// while let Some((pipe, len)) = device.read_into(&mut buffer)? {
+    ///     println!("Pipe {}: {:?}", pipe, &buffer[..len]);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors and a custom io::ErrorKind::InvalidInput if `buffer` is too
+    /// small for the packet, which then stays in the queue.
+    pub fn read_into(&mut self, buffer: &mut [u8]) -> io::Result<Option<(u8, usize)>> {
+        self.clear_rx_dr()?;
+        self.read_payload(buffer)
+    }
+
+    /// Iterate over the packets in the receiver queue.
+    ///
+    /// Like `.read_all(...)`, the device is put in standby while the queue is read, and
+    /// the previous state is restored when the iterator is dropped. The iteration ends
+    /// when the queue is empty or on the first error.
+    ///
+    /// ```rust,no_run
+    /// # use nrf24l01::NRF24L01;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let mut device = NRF24L01::new(25, 0)?;
+    /// let mut payloads = Vec::new();
+    /// for packet in device.packets()? {
+    ///     payloads.push(packet?.payload().to_vec());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors.
+    pub fn packets(&mut self) -> io::Result<Packets<'_>> {
+        self.supervise()?;
+        self.suspend_listening()?;
+        Ok(Packets {
+            device: self,
+            count: 0,
+            done: false,
+        })
+    }
+
+    /// Queue (FIFO) data to be sent, one packet at a time.
+    ///
+    /// In TX mode, `pipe_num` is ignored. In RX mode, this function queues an ACK payload
+    /// for the next message to arrive on `pipe_num`. if `pipe_num` is bigger than 5,
     /// it is capped to 5.
     ///
     /// The maximum size for a packet is 32 bytes.
@@ -631,17 +1766,17 @@ impl NRF24L01 {
     /// transaction:
     ///
     /// * In TX mode, a payload is removed from the send queue if and only if
-    /// it has been successfully sent, that is, an ACK (with or without payload) has
-    /// been received for it.
+    ///   it has been successfully sent, that is, an ACK (with or without payload) has
+    ///   been received for it.
     ///
     /// * In RX mode, an ACK payload is removed from the queue if and only if
-    /// it has been sent AND the pipe receives a new message, different from
-    /// the one the ACK payload responded to. This is because the receiver has
-    /// no mean to know whether the transmitter has received the ACK until
-    /// it receives a new, different message from the same transmitter.
-    /// So, it keeps the ACK payload under hand in case the transmitter resends the same
-    /// packet over again.
-    pub fn push(&self, pipe_num: u8, data: &[u8]) -> io::Result<()> {
+    ///   it has been sent AND the pipe receives a new message, different from
+    ///   the one the ACK payload responded to. This is because the receiver has
+    ///   no mean to know whether the transmitter has received the ACK until
+    ///   it receives a new, different message from the same transmitter.
+    ///   So, it keeps the ACK payload under hand in case the transmitter resends the same
+    ///   packet over again.
+    pub fn push(&mut self, pipe_num: u8, data: &[u8]) -> io::Result<()> {
         let (status, fifo_status) = self.read_register(FIFO_STATUS)?;
         if (status & 1 != 0) || (fifo_status & 0b0010_0000 != 0) {
             // TX_FIFO is full
@@ -666,66 +1801,460 @@ impl NRF24L01 {
                 let ubound = data.len() + 1;
                 out_buffer[1..ubound].copy_from_slice(data);
                 let mut in_buffer = [0u8; 33];
-                self.send_command(&out_buffer[..ubound], &mut in_buffer[..ubound])
+                self.send_command(&out_buffer[..ubound], &mut in_buffer[..ubound])?;
+                if !self.is_receiver() {
+                    self.tx_queue.push_back(data.to_vec());
+                }
+                Ok(())
             }
         }
     }
 
-    /// [TX mode only] Send all packets in the TX FIFO queue.
+    // Same as `.push(...)` for an ACK payload, rejecting the pipes that don't exist
+    // instead of capping them to 5.
+    pub(crate) fn push_ack_payload(&mut self, pipe_num: u8, data: &[u8]) -> io::Result<()> {
+        if pipe_num > 5 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Pipe {} doesn't exist, pipes are numbered 0 to 5", pipe_num),
+            ));
+        }
+        self.push(pipe_num, data)
+    }
+
+    /// [TX mode only] Send all packets in the TX FIFO queue.
     ///
     /// The call blocks until all packets are sent or the device reaches
-    /// the `max_retries` number of retries after failure.
+    /// the `max_retries` number of retries after failure. It gives up if the
+    /// packets are not completed within 1 s for each packet queued when it starts.
     /// Return the number of retries in case of success.
     ///
-    /// The payloads that failed to be sent remain in the TX queue.
+    /// The packets waiting in the software queue (see `.enqueue(...)`) are sent too.
+    ///
+    /// By default, the payloads that failed to be sent remain in the TX queue.
     /// You can call `.send()` again to relaunch a send/retry cycle or
-    /// call `.flush_output` to clear the queue.
+    /// call `.flush_output` to clear the queue. See `.set_max_retries_policy(...)`
+    /// to have them dropped instead.
+    ///
+    /// See `.send_timeout(...)` for a different time limit and `.start_send()`
+    /// for a non-blocking transmission.
     ///
     /// # Errors
     /// Return Spidev errors as well as a custom io::ErrorKind::Timeout
-    /// when the maximun number of retries has been reached.
+    /// when the maximun number of retries has been reached, a custom
+    /// io::ErrorKind::NotConnected if the device is powered down or the time limit
+    /// has expired, and a custom io::ErrorKind::InvalidInput in RX mode.
+    ///
+    /// In supervisor mode, return a custom io::ErrorKind::Interrupted if the
+    /// configuration had to be restored while packets were in the TX FIFO: the
+    /// device lost them.
     pub fn send(&mut self) -> io::Result<u8> {
+        let timeout = self.send_time_limit();
+        self.send_timeout(timeout)
+    }
+
+    /// [TX mode only] Same as `.send()`, giving up if the packets are not all
+    /// completed within `timeout`.
+    ///
+    /// The device never reports completion if it is disconnected, so the
+    /// timeout is the only way to detect it.
+    ///
+    /// # Errors
+    ///
+    /// Same as `.send()`.
+    pub fn send_timeout(&mut self, timeout: Duration) -> io::Result<u8> {
+        let policy = self.max_retries_policy;
+        self.send_with(policy, timeout)
+    }
+
+    /// [TX mode only] Same as `.send()`, applying `policy` instead of the device
+    /// `MaxRetriesPolicy` on failure.
+    ///
+    /// # Errors
+    ///
+    /// Same as `.send()`.
+    pub fn send_with_policy(&mut self, policy: MaxRetriesPolicy) -> io::Result<u8> {
+        let timeout = self.send_time_limit();
+        self.send_with(policy, timeout)
+    }
+
+    // Time limit of `.send()`, bounded by the packets queued when it starts.
+    fn send_time_limit(&self) -> Duration {
+        let packets = u32::try_from(self.queue_len()).unwrap_or(u32::MAX);
+        SEND_TIMEOUT.saturating_mul(packets.max(1))
+    }
+
+    fn send_with(&mut self, policy: MaxRetriesPolicy, timeout: Duration) -> io::Result<u8> {
+        match self.complete_send(timeout)? {
+            SendStatus::Sent { retries } => Ok(retries),
+            _ => {
+                self.apply_max_retries_policy(policy)?;
+                Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Maximum number of retries reached!",
+                ))
+            }
+        }
+    }
+
+    /// [TX mode only] Same as `.send()`, but report the outcome of each packet.
+    ///
+    /// The outcomes are in the order the packets were pushed. Reaching the maximum
+    /// number of retries is not an error: the failed packet and the following ones are
+    /// reported as `PacketOutcome::Failed` and `PacketOutcome::Unsent`, and the
+    /// device `MaxRetriesPolicy` is applied.
+    ///
+    /// Neither is exceeding the time limit of `.send()`: the pending packet is reported
+    /// as `PacketOutcome::Failed` as well, but the queue is left untouched.
+    ///
+    /// Only the packets pushed in TX mode are accounted for.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors, a custom io::ErrorKind::NotConnected if the device
+    /// is powered down and a custom io::ErrorKind::InvalidInput in RX mode.
+    pub fn send_detailed(&mut self) -> io::Result<Vec<PacketOutcome>> {
+        let timeout = self.send_time_limit();
+        self.start_send()?;
+        let status = self.wait_send(timeout)?;
+        let outcomes = NRF24L01::packet_outcomes(
+            &self.delivered,
+            status,
+            self.tx_queue.len(),
+            self.backlog.len(),
+        );
+        if status == Some(SendStatus::Failed) {
+            let policy = self.max_retries_policy;
+            self.apply_max_retries_policy(policy)?;
+        }
+        Ok(outcomes)
+    }
+
+    // Outcome of each packet once the transmission has ended with `status`, `None`
+    // on timeout: `queued` packets are left in the TX FIFO, `backlog` in the software
+    // queue, and the head of the TX FIFO is the one that failed.
+    fn packet_outcomes(
+        delivered: &[u8],
+        status: Option<SendStatus>,
+        queued: usize,
+        backlog: usize,
+    ) -> Vec<PacketOutcome> {
+        let mut outcomes: Vec<PacketOutcome> = delivered
+            .iter()
+            .map(|&retries| PacketOutcome::Delivered { retries })
+            .collect();
+        if !matches!(status, Some(SendStatus::Sent { .. })) {
+            outcomes.push(PacketOutcome::Failed);
+            let unsent = queued.saturating_sub(1) + backlog;
+            outcomes.extend((0..unsent).map(|_| PacketOutcome::Unsent));
+        }
+        outcomes
+    }
+
+    // Start sending and poll until done, return `Sent` or `Failed`.
+    fn complete_send(&mut self, timeout: Duration) -> io::Result<SendStatus> {
+        self.start_send()?;
+        self.wait_send(timeout)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotConnected,
+                "Transmission timed out, is the device connected?",
+            )
+        })
+    }
+
+    // Poll the transmission until done, return `None` if it is not completed
+    // within `timeout`.
+    fn wait_send(&mut self, timeout: Duration) -> io::Result<Option<SendStatus>> {
+        let started = Instant::now();
+        loop {
+            // wait at least 360us
+            sleep(Duration::new(0, 360_000));
+            match self.poll_transmission()? {
+                SendStatus::Pending => {
+                    if started.elapsed() > timeout {
+                        self.abort_send()?;
+                        return Ok(None);
+                    }
+                }
+                status => return Ok(Some(status)),
+            }
+        }
+    }
+
+    /// [TX mode only] Start sending the packets in the TX queue and return immediately.
+    ///
+    /// Call `.poll_send()` until it no longer returns `SendStatus::Pending` to
+    /// complete the transmission.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors, a custom io::ErrorKind::NotConnected if the device
+    /// is powered down and a custom io::ErrorKind::InvalidInput in RX mode. In
+    /// supervisor mode, return a custom io::ErrorKind::Interrupted if the configuration
+    /// had to be restored while packets were in the TX FIFO.
+    pub fn start_send(&mut self) -> io::Result<()> {
+        if self.is_receiver() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot send in RX mode, call enter_tx() first!",
+            ));
+        }
+        self.supervise_send()?;
+        self.wait_for_standby()?;
+        self.switch_rf_path(RfPath::Transmit)?;
         // clear TX_DS and MAX_RT
         self.write_register(STATUS, 0x30)?;
-        // init retry counter
-        let mut counter = 0u8;
+        self.sending = true;
+        self.delivered.clear();
+        self.fill_fifo()?;
+        self.send_next()
+    }
+
+    // Pulse CE to send the next packet, if any.
+    fn send_next(&mut self) -> io::Result<()> {
         let (_, fifo_status) = self.read_register(FIFO_STATUS)?;
-        let mut packets_left = fifo_status & 0x10 == 0;
-        while packets_left {
+        if fifo_status & 0x10 == 0 {
             // send with a 10us pulse
             self.ce.up()?;
             sleep(Duration::new(0, 10_000));
             self.ce.down()?;
-            let mut status = 0u8;
-            let mut observe = 0u8;
-            // wait for ACK
-            while status & 0x30 == 0 {
-                // wait at least 360us
-                sleep(Duration::new(0, 360_000));
-                let outcome = self.read_register(OBSERVE_TX)?;
-                status = outcome.0;
-                observe = outcome.1;
+        }
+        Ok(())
+    }
+
+    /// [TX mode only] Check the progress of the transmission started by `.start_send()`.
+    ///
+    /// Each call that finds a packet acknowledged starts sending the next one.
+    /// The device should be polled every few hundred µs.
+    ///
+    /// On failure, the device `MaxRetriesPolicy` is applied.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors and a custom io::ErrorKind::InvalidInput if no
+    /// transmission is in progress.
+    pub fn poll_send(&mut self) -> io::Result<SendStatus> {
+        let status = self.poll_transmission()?;
+        if status == SendStatus::Failed {
+            let policy = self.max_retries_policy;
+            self.apply_max_retries_policy(policy)?;
+        }
+        Ok(status)
+    }
+
+    fn poll_transmission(&mut self) -> io::Result<SendStatus> {
+        if !self.sending {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "No transmission in progress, call start_send() first!",
+            ));
+        }
+        // read before STATUS, a packet leaves the queue once acknowledged
+        let (_, fifo_status) = self.read_register(FIFO_STATUS)?;
+        let (status, observe) = self.read_register(OBSERVE_TX)?;
+        // check MAX_RT
+        if status & 0x10 > 0 {
+            // failure
+            // clear MAX_RT
+            self.write_register(STATUS, 0x10)?;
+            self.abort_send()?;
+            return Ok(SendStatus::Failed);
+        }
+        // check TX_DS
+        if status & 0x20 == 0 {
+            if fifo_status & 0x10 == 0 {
+                return Ok(SendStatus::Pending);
+            }
+            // nothing left to send
+            self.abort_send()?;
+            return Ok(self.sent());
+        }
+        // Success, clear TX_DS
+        self.write_register(STATUS, 0x20)?;
+        self.delivered.push(observe & 0x0f);
+        self.tx_queue.pop_front();
+        self.fill_fifo()?;
+        let (_, fifo_status) = self.read_register(FIFO_STATUS)?;
+        if fifo_status & 0x10 == 0 {
+            self.send_next()?;
+            Ok(SendStatus::Pending)
+        } else {
+            self.abort_send()?;
+            Ok(self.sent())
+        }
+    }
+
+    /// [TX mode only] Send `packets` as fast as possible, for bulk transfers.
+    ///
+    /// Contrary to `.send()`, which sends one packet at a time, CE is kept high and
+    /// the TX FIFO is refilled as soon as a packet leaves it, so that the device never
+    /// waits. The packets already queued, in the TX FIFO and in the software queue,
+    /// are sent first.
+    ///
+    /// Return the number of packets and bytes delivered and the time it took.
+    ///
+    /// ```rust,no_run
+    /// # use nrf24l01::NRF24L01;
+    /// # let mut device = NRF24L01::new(25, 0).unwrap();
+    /// let log = vec![0u8; 4096];
+    /// let report = device.stream(log.chunks(32)).unwrap();
+    /// println!("{:.0} bytes/s", report.throughput());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Same as `.send()`, and a custom io::ErrorKind::InvalidData if a packet is bigger
+    /// than 32 bytes. On failure, the device `MaxRetriesPolicy` is applied and the
+    /// packets not taken from `packets` yet are left there.
+    ///
+    /// **Note**: the device reports deliveries with a single flag, so the driver may
+    /// miss one when two packets are delivered in quick succession. If a packet then
+    /// fails, `MaxRetriesPolicy::DropFailed` may drop the packet before it instead,
+    /// and `.queue_len()` may count a delivered packet. Use `.send()` when every
+    /// failure must be accounted for exactly.
+    pub fn stream<I, P>(&mut self, packets: I) -> io::Result<StreamReport>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        if self.is_receiver() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot send in RX mode, call enter_tx() first!",
+            ));
+        }
+        self.supervise_send()?;
+        self.wait_for_standby()?;
+        self.switch_rf_path(RfPath::Transmit)?;
+        // clear TX_DS and MAX_RT
+        self.write_register(STATUS, 0x30)?;
+        let started = Instant::now();
+        let result = self.stream_packets(packets.into_iter());
+        self.ce.down()?;
+        self.switch_rf_path(RfPath::Idle)?;
+        let (packets, bytes) = result?;
+        Ok(StreamReport {
+            packets,
+            bytes,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    fn stream_packets<I, P>(&mut self, mut packets: I) -> io::Result<(usize, usize)>
+    where
+        I: Iterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut count = self.tx_queue.len();
+        let mut bytes: usize = self.tx_queue.iter().map(Vec::len).sum();
+        let mut exhausted = false;
+        let mut last_progress = Instant::now();
+        self.ce.up()?;
+        loop {
+            let (status, fifo_status) = self.read_register(FIFO_STATUS)?;
+            // check TX_DS, before MAX_RT which may be raised by the next packet
+            if NRF24L01::track_tx_fifo(&mut self.tx_queue, status, fifo_status) {
+                self.write_register(STATUS, 0x20)?;
+                last_progress = Instant::now();
             }
             // check MAX_RT
             if status & 0x10 > 0 {
-                // failure
-                // clear MAX_RT
+                // stop before clearing MAX_RT, which resumes the transmission
+                self.ce.down()?;
                 self.write_register(STATUS, 0x10)?;
-                // force return
+                let policy = self.max_retries_policy;
+                self.apply_max_retries_policy(policy)?;
                 return Err(io::Error::new(
                     io::ErrorKind::TimedOut,
                     "Maximum number of retries reached!",
                 ));
+            }
+            if fifo_status & 0x10 > 0 && exhausted {
+                // TX FIFO empty, everything pushed has been delivered
+                return Ok((count, bytes));
+            }
+            let next = if status & 1 > 0 {
+                // TX FIFO full
+                None
+            } else if let Some(payload) = self.backlog.pop_front() {
+                Some(payload)
+            } else if exhausted {
+                None
+            } else {
+                let payload = packets.next().map(|packet| packet.as_ref().to_vec());
+                exhausted = payload.is_none();
+                payload
             };
-            // Success
-            counter += observe & 0x0f;
-            let (_, fifo_status) = self.read_register(FIFO_STATUS)?;
-            packets_left = fifo_status & 0x10 == 0;
+            match next {
+                Some(payload) => {
+                    self.push(0, &payload)?;
+                    count += 1;
+                    bytes += payload.len();
+                }
+                None => {
+                    if last_progress.elapsed() > SEND_TIMEOUT {
+                        return Err(io::Error::new(
+                            io::ErrorKind::NotConnected,
+                            "Transmission timed out, is the device connected?",
+                        ));
+                    }
+                    sleep(Duration::from_micros(50));
+                }
+            }
+        }
+    }
+
+    // Keep the shadow of the TX FIFO in sync while streaming: each TX_DS means
+    // its head has been delivered, and an empty or full FIFO tells its exact
+    // content. Return `true` if a packet has been delivered.
+    //
+    // TX_DS is a single flag: if two packets are delivered between two polls while
+    // the FIFO is neither empty nor full, the shadow keeps a delivered packet at its
+    // head until the next resync. A MAX_RT in that window makes `DropFailed` drop the
+    // delivered packet and send the failed one again.
+    fn track_tx_fifo(tx_queue: &mut VecDeque<Vec<u8>>, status: u8, fifo_status: u8) -> bool {
+        let delivered = status & 0x20 > 0;
+        if delivered {
+            tx_queue.pop_front();
+        }
+        if fifo_status & 0x10 > 0 {
+            tx_queue.clear();
+        } else if status & 1 > 0 {
+            let delivered = tx_queue.len().saturating_sub(3);
+            tx_queue.drain(..delivered);
+        }
+        delivered
+    }
+
+    fn sent(&self) -> SendStatus {
+        let retries = self
+            .delivered
+            .iter()
+            .fold(0u8, |total, &retries| total.saturating_add(retries));
+        SendStatus::Sent { retries }
+    }
+
+    fn apply_max_retries_policy(&mut self, policy: MaxRetriesPolicy) -> io::Result<()> {
+        match policy {
+            MaxRetriesPolicy::Keep => Ok(()),
+            MaxRetriesPolicy::DropFailed => {
+                // The FIFO can't drop its head: flush and push the other packets again
+                let queue = std::mem::take(&mut self.tx_queue);
+                self.flush_fifo()?;
+                for payload in queue.iter().skip(1) {
+                    self.push(0, payload)?;
+                }
+                self.fill_fifo()
+            }
+            MaxRetriesPolicy::FlushAll => self.flush_output(),
         }
-        // clear TX_DS
-        self.write_register(STATUS, 0x20)?;
-        // if all sent, return retry counter
-        Ok(counter)
+    }
+
+    // End the transmission in progress.
+    fn abort_send(&mut self) -> io::Result<()> {
+        self.sending = false;
+        self.ce.down()?;
+        self.switch_rf_path(RfPath::Idle)
     }
 
     /// Clear input queue.
@@ -737,14 +2266,146 @@ impl NRF24L01 {
         Ok(())
     }
 
-    /// Clear output queue.
+    /// Clear output queue, including the software queue.
     ///
     /// In RX mode, use only when device is in standby.
-    pub fn flush_output(&self) -> io::Result<()> {
+    pub fn flush_output(&mut self) -> io::Result<()> {
+        self.backlog.clear();
+        self.flush_fifo()
+    }
+
+    fn flush_fifo(&mut self) -> io::Result<()> {
         let mut buffer = [0u8];
         self.send_command(&[FLUSH_TX], &mut buffer)?;
+        self.tx_queue.clear();
         Ok(())
     }
+
+    /// [TX mode only] Queue data to be sent, beyond the 3 packets of the TX FIFO.
+    ///
+    /// The packet goes to a software queue that feeds the TX FIFO as slots free up,
+    /// either right away or during `.send()`.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors, a custom io::ErrorKind::WouldBlock if the software queue
+    /// is full (see `.set_queue_capacity(...)`), a custom io::ErrorKind::InvalidData if
+    /// the packet is bigger than 32 bytes and a custom io::ErrorKind::InvalidInput in RX mode.
+    pub fn enqueue(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.is_receiver() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot enqueue in RX mode, call enter_tx() first!",
+            ));
+        }
+        NRF24L01::queue_packet(&mut self.backlog, self.backlog_capacity, data)?;
+        self.fill_fifo()
+    }
+
+    // Append `data` to the software queue, unless it is full.
+    fn queue_packet(
+        backlog: &mut VecDeque<Vec<u8>>,
+        capacity: Option<usize>,
+        data: &[u8],
+    ) -> io::Result<()> {
+        if data.len() > 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Packet too big!",
+            ));
+        }
+        if let Some(capacity) = capacity {
+            if backlog.len() >= capacity {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "Transmit queue is full!",
+                ));
+            }
+        }
+        backlog.push_back(data.to_vec());
+        Ok(())
+    }
+
+    /// Set the maximum number of packets waiting in the software queue, not counting the
+    /// ones in the TX FIFO, or `None` for no limit (default).
+    pub fn set_queue_capacity(&mut self, capacity: Option<usize>) {
+        self.backlog_capacity = capacity;
+    }
+
+    /// Number of packets waiting to be sent, in the TX FIFO and in the software queue.
+    ///
+    /// Only the packets pushed in TX mode are accounted for.
+    pub fn queue_len(&self) -> usize {
+        self.tx_queue.len() + self.backlog.len()
+    }
+
+    // Move packets from the software queue to the TX FIFO while it has room.
+    fn fill_fifo(&mut self) -> io::Result<()> {
+        if self.is_receiver() {
+            return Ok(());
+        }
+        while let Some(payload) = self.backlog.pop_front() {
+            let (status, fifo_status) = self.read_register(FIFO_STATUS)?;
+            if (status & 1 != 0) || (fifo_status & 0b0010_0000 != 0) {
+                // TX_FIFO is full
+                self.backlog.push_front(payload);
+                break;
+            }
+            self.push(0, &payload)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the received packets, see `NRF24L01::packets()`.
+pub struct Packets<'a> {
+    device: &'a mut NRF24L01,
+    count: usize,
+    done: bool,
+}
+
+impl Iterator for Packets<'_> {
+    type Item = io::Result<Packet>;
+
+    fn next(&mut self) -> Option<io::Result<Packet>> {
+        if self.done {
+            return None;
+        }
+        if self.count == RX_FIFO_DEPTH {
+            self.done = true;
+            return self.device.check_input_drained().err().map(Err);
+        }
+        self.count += 1;
+        let packet = self.device.read_packet().transpose();
+        self.done = !matches!(packet, Some(Ok(_)));
+        packet
+    }
+}
+
+impl Drop for Packets<'_> {
+    fn drop(&mut self) {
+        // errors can't be reported from here
+        let _ = self.device.resume_listening();
+    }
+}
+
+impl Drop for NRF24L01 {
+    fn drop(&mut self) {
+        if self.power_down_on_drop {
+            // errors can't be reported from here
+            let _ = self.power_down();
+            let _ = self.ce.release();
+            self.release_pa_lna_pins();
+            if let Some(ref mut irq) = self.irq {
+                let _ = irq.release();
+            }
+        } else {
+            self.ce.keep_on_drop();
+            for pin in self.tx_enable.iter_mut().chain(self.rx_enable.iter_mut()) {
+                pin.keep_on_drop();
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -782,6 +2443,242 @@ mod tests {
         assert_eq!(tx_conf.pa_level, PALevel::Min);
         assert_eq!(tx_conf.max_retries, 0);
         assert_eq!(tx_conf.retry_delay, 0);
+        assert!(!tx_conf.auto_retry_delay);
+        assert_eq!(tx_conf.max_ack_payload, 0);
         assert_eq!(tx_conf.pipe0_address, [0u8; 5]);
     }
+
+    fn register_value(
+        registers: &[(Register, RegisterValue)],
+        register: Register,
+    ) -> RegisterValue {
+        registers
+            .iter()
+            .find(|&&(reg, _)| reg == register)
+            .map(|&(_, value)| value)
+            .unwrap()
+    }
+
+    #[test]
+    fn rx_registers() {
+        let rx_conf = RXConfig {
+            channel: 200,
+            data_rate: DataRate::R250Kbps,
+            pa_level: PALevel::High,
+            pipe0_address: *b"0node",
+            pipe1_address: Some(*b"1node"),
+            pipe3_addr_lsb: Some(b'3'),
+            ..Default::default()
+        };
+        let mut registers = Vec::new();
+        let base_config =
+            NRF24L01::configure_mode(&OperatingMode::RX(rx_conf), &mut registers).unwrap();
+        assert_eq!(base_config, 0b0011_1101);
+        assert_eq!(register_value(&registers, RF_CH), RegisterValue::Byte(125));
+        assert_eq!(
+            register_value(&registers, RF_SETUP),
+            RegisterValue::Byte(0b0010_0100)
+        );
+        assert_eq!(
+            register_value(&registers, EN_RXADDR),
+            RegisterValue::Byte(0b0000_1011)
+        );
+        assert_eq!(
+            register_value(&registers, RX_ADDR_P1),
+            RegisterValue::Address(*b"1node")
+        );
+        assert_eq!(
+            register_value(&registers, RX_ADDR_P3),
+            RegisterValue::Byte(b'3')
+        );
+    }
+
+    #[test]
+    fn tx_registers() {
+        let tx_conf = TXConfig {
+            pipe0_address: *b"abcde",
+            max_retries: 20,
+            retry_delay: 2,
+            ..Default::default()
+        };
+        let mut registers = Vec::new();
+        let base_config =
+            NRF24L01::configure_mode(&OperatingMode::TX(tx_conf), &mut registers).unwrap();
+        assert_eq!(base_config, 0b0100_1100);
+        assert_eq!(
+            register_value(&registers, SETUP_RETR),
+            RegisterValue::Byte(0x2F)
+        );
+        assert_eq!(
+            register_value(&registers, TX_ADDR),
+            RegisterValue::Address(*b"abcde")
+        );
+        assert_eq!(
+            register_value(&registers, EN_RXADDR),
+            RegisterValue::Byte(1)
+        );
+    }
+
+    #[test]
+    fn lna_gain() {
+        assert_eq!(
+            NRF24L01::setup_rf(DataRate::R2Mbps, PALevel::Max, false),
+            0b0000_1110
+        );
+        let tx_conf = TXConfig {
+            pipe0_address: *b"abcde",
+            lna_gain: true,
+            ..Default::default()
+        };
+        let mut registers = Vec::new();
+        NRF24L01::configure_mode(&OperatingMode::TX(tx_conf), &mut registers).unwrap();
+        assert_eq!(
+            register_value(&registers, RF_SETUP),
+            RegisterValue::Byte(0b0000_0001)
+        );
+    }
+
+    #[test]
+    fn payload_widths() {
+        assert_eq!(NRF24L01::payload_width(1), Some(1));
+        assert_eq!(NRF24L01::payload_width(32), Some(32));
+        assert_eq!(NRF24L01::payload_width(0), None);
+        assert_eq!(NRF24L01::payload_width(33), None);
+        assert_eq!(NRF24L01::payload_width(0xFF), None);
+    }
+
+    #[test]
+    fn packet_payload() {
+        let mut packet = Packet {
+            pipe: 2,
+            len: 5,
+            data: [0u8; 32],
+        };
+        packet.data[..5].copy_from_slice(b"hello");
+        assert_eq!(packet.pipe(), 2);
+        assert_eq!(packet.payload(), b"hello");
+    }
+
+    #[test]
+    fn stream_throughput() {
+        let report = StreamReport {
+            packets: 64,
+            bytes: 2048,
+            elapsed: Duration::from_millis(250),
+        };
+        assert_eq!(report.throughput(), 8192.0);
+        let empty = StreamReport {
+            packets: 0,
+            bytes: 0,
+            elapsed: Duration::from_secs(0),
+        };
+        assert_eq!(empty.throughput(), 0.0);
+    }
+
+    #[test]
+    fn interrupt_masks() {
+        let rx_conf = RXConfig {
+            pipe0_address: *b"0node",
+            interrupts: Some(Interrupts::NONE),
+            ..Default::default()
+        };
+        let base_config =
+            NRF24L01::configure_mode(&OperatingMode::RX(rx_conf), &mut Vec::new()).unwrap();
+        assert_eq!(base_config, 0b0111_1101);
+        let tx_conf = TXConfig {
+            pipe0_address: *b"abcde",
+            interrupts: Some(Interrupts {
+                max_rt: true,
+                ..Interrupts::NONE
+            }),
+            ..Default::default()
+        };
+        let base_config =
+            NRF24L01::configure_mode(&OperatingMode::TX(tx_conf), &mut Vec::new()).unwrap();
+        assert_eq!(base_config, 0b0110_1100);
+    }
+
+    #[test]
+    fn tx_retry_delay() {
+        let mut tx_conf = TXConfig {
+            data_rate: DataRate::R250Kbps,
+            pipe0_address: *b"abcde",
+            max_ack_payload: 32,
+            retry_delay: 4,
+            ..Default::default()
+        };
+        let mut registers = Vec::new();
+        let mode = OperatingMode::TX(tx_conf.clone());
+        assert!(NRF24L01::configure_mode(&mode, &mut registers).is_err());
+        tx_conf.auto_retry_delay = true;
+        let mode = OperatingMode::TX(tx_conf);
+        NRF24L01::configure_mode(&mode, &mut registers).unwrap();
+        assert_eq!(
+            register_value(&registers, SETUP_RETR),
+            RegisterValue::Byte(0x50)
+        );
+    }
+
+    #[test]
+    fn rx_to_tx_retransmission() {
+        let rf_setup = NRF24L01::setup_rf(DataRate::R250Kbps, PALevel::Max, false);
+        assert_eq!(NRF24L01::fallback_retransmission(rf_setup), 0x53);
+        let rf_setup = NRF24L01::setup_rf(DataRate::R2Mbps, PALevel::Low, true);
+        assert_eq!(NRF24L01::fallback_retransmission(rf_setup), 0x13);
+    }
+
+    #[test]
+    fn stream_bookkeeping() {
+        let mut queue: VecDeque<Vec<u8>> = vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]
+            .into_iter()
+            .collect();
+        // a delivered, b failed
+        assert!(NRF24L01::track_tx_fifo(&mut queue, 0x30, 0));
+        assert_eq!(queue, vec![b"b".to_vec(), b"c".to_vec()]);
+        // still retrying b
+        assert!(!NRF24L01::track_tx_fifo(&mut queue, 0, 0));
+        assert_eq!(queue.len(), 2);
+        // more packets pushed than the FIFO can hold, missed TX_DS
+        queue.extend(vec![b"d".to_vec(), b"e".to_vec()]);
+        assert!(!NRF24L01::track_tx_fifo(&mut queue, 0x01, 0x20));
+        assert_eq!(queue, vec![b"c".to_vec(), b"d".to_vec(), b"e".to_vec()]);
+        // all delivered
+        assert!(NRF24L01::track_tx_fifo(&mut queue, 0x20, 0x10));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn send_outcomes() {
+        use PacketOutcome::*;
+        let sent = Some(SendStatus::Sent { retries: 3 });
+        assert_eq!(
+            NRF24L01::packet_outcomes(&[1, 2], sent, 0, 0),
+            vec![Delivered { retries: 1 }, Delivered { retries: 2 }]
+        );
+        // MAX_RT on the second packet, with one more in the TX FIFO and 2 in the backlog
+        assert_eq!(
+            NRF24L01::packet_outcomes(&[0], Some(SendStatus::Failed), 2, 2),
+            vec![Delivered { retries: 0 }, Failed, Unsent, Unsent, Unsent]
+        );
+        // timeout on the first packet
+        assert_eq!(
+            NRF24L01::packet_outcomes(&[], None, 3, 0),
+            vec![Failed, Unsent, Unsent]
+        );
+    }
+
+    #[test]
+    fn queue_capacity() {
+        let mut backlog = VecDeque::new();
+        NRF24L01::queue_packet(&mut backlog, Some(2), b"a").unwrap();
+        NRF24L01::queue_packet(&mut backlog, Some(2), b"b").unwrap();
+        let err = NRF24L01::queue_packet(&mut backlog, Some(2), b"c").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(backlog, vec![b"a".to_vec(), b"b".to_vec()]);
+        // no limit
+        NRF24L01::queue_packet(&mut backlog, None, b"c").unwrap();
+        assert_eq!(backlog.len(), 3);
+        let err = NRF24L01::queue_packet(&mut backlog, None, &[0u8; 33]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
 }
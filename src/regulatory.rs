@@ -0,0 +1,142 @@
+// Copyright 2017, Romuald Texier-Marcadé <romualdtm@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Regulatory profiles restricting the channels and power levels in use.
+
+use std::io;
+
+use crate::{OperatingMode, PALevel};
+
+/// A regulatory profile, restricting the channels and power levels the device may use.
+///
+/// The device accepts channels up to 2525 MHz, but the 2.4 GHz ISM band ends at 2483.5 MHz
+/// in most jurisdictions. Set a profile with `NRF24L01::set_regulatory_profile(...)`
+/// so that `.configure(...)` rejects out of band settings and `.scan(...)` skips
+/// out of band channels.
+///
+/// The provided profiles are conservative starting points, keeping a margin from
+/// the band edges. Check your local regulations, and lower `max_pa_level` for
+/// modules with an external power amplifier or a high gain antenna.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct RegulatoryProfile {
+    /// Lowest allowed channel.
+    pub min_channel: u8,
+    /// Highest allowed channel.
+    pub max_channel: u8,
+    /// Highest allowed power level.
+    pub max_pa_level: PALevel,
+}
+
+impl RegulatoryProfile {
+    /// All channels and power levels supported by the device (default).
+    pub const UNRESTRICTED: RegulatoryProfile = RegulatoryProfile {
+        min_channel: 0,
+        max_channel: 125,
+        max_pa_level: PALevel::Max,
+    };
+
+    /// Europe (ETSI EN 300 328): 2401 to 2482 MHz.
+    pub const ETSI: RegulatoryProfile = RegulatoryProfile {
+        min_channel: 1,
+        max_channel: 82,
+        max_pa_level: PALevel::Max,
+    };
+
+    /// United States (FCC part 15.247): 2402 to 2481 MHz, keeping away from the
+    /// restricted band above 2483.5 MHz.
+    pub const FCC: RegulatoryProfile = RegulatoryProfile {
+        min_channel: 2,
+        max_channel: 81,
+        max_pa_level: PALevel::Max,
+    };
+
+    /// Is `channel` allowed?
+    pub fn allows_channel(&self, channel: u8) -> bool {
+        self.min_channel <= channel && channel <= self.max_channel
+    }
+
+    /// Check `mode` only uses allowed channels and power levels.
+    ///
+    /// # Errors
+    ///
+    /// A custom io::ErrorKind::InvalidInput if it doesn't.
+    pub fn check(&self, mode: &OperatingMode) -> io::Result<()> {
+        let (channel, pa_level) = match *mode {
+            OperatingMode::RX(ref config) => (config.channel, config.pa_level),
+            OperatingMode::TX(ref config) => (config.channel, config.pa_level),
+        };
+        // as applied by `configure`
+        let channel = channel.min(125);
+        if !self.allows_channel(channel) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Channel {} is not allowed, the regulatory profile restricts channels to [{}, {}]",
+                    channel, self.min_channel, self.max_channel
+                ),
+            ));
+        }
+        if pa_level > self.max_pa_level {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Power level {:?} is not allowed, the regulatory profile limits it to {:?}",
+                    pa_level, self.max_pa_level
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for RegulatoryProfile {
+    fn default() -> RegulatoryProfile {
+        RegulatoryProfile::UNRESTRICTED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RXConfig, TXConfig};
+
+    #[test]
+    fn profile_channels() {
+        assert!(RegulatoryProfile::UNRESTRICTED.allows_channel(125));
+        assert!(RegulatoryProfile::ETSI.allows_channel(82));
+        assert!(!RegulatoryProfile::ETSI.allows_channel(108));
+        assert!(!RegulatoryProfile::FCC.allows_channel(0));
+        let mode = OperatingMode::RX(RXConfig {
+            channel: 108,
+            ..Default::default()
+        });
+        assert!(RegulatoryProfile::default().check(&mode).is_ok());
+        let capped = OperatingMode::RX(RXConfig {
+            channel: 200,
+            ..Default::default()
+        });
+        assert!(RegulatoryProfile::default().check(&capped).is_ok());
+        assert!(RegulatoryProfile::ETSI.check(&mode).is_err());
+    }
+
+    #[test]
+    fn profile_pa_level() {
+        let profile = RegulatoryProfile {
+            max_pa_level: PALevel::Low,
+            ..RegulatoryProfile::ETSI
+        };
+        let mut config = TXConfig {
+            channel: 76,
+            pa_level: PALevel::Low,
+            ..Default::default()
+        };
+        assert!(profile.check(&OperatingMode::TX(config.clone())).is_ok());
+        config.pa_level = PALevel::High;
+        assert!(profile.check(&OperatingMode::TX(config)).is_err());
+    }
+}
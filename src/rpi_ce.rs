@@ -1,6 +1,8 @@
 extern crate rppal;
 
 use std::io;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
 use self::rppal::gpio::{Level, Mode, GPIO};
 
@@ -18,7 +20,7 @@ impl CEPin {
         let mut gpio = GPIO::new().unwrap();
         gpio.set_mode(pin_num8, Mode::Output);
         Ok(CEPin {
-            gpio: gpio,
+            gpio,
             ce_pin: pin_num8,
             value: Level::Low,
             saved_value: Level::Low,
@@ -37,7 +39,7 @@ impl CEPin {
         Ok(())
     }
 
-    pub fn save_state(&mut self) -> () {
+    pub fn save_state(&mut self) {
         self.saved_value = self.value;
     }
 
@@ -46,4 +48,58 @@ impl CEPin {
         self.value = self.saved_value;
         Ok(())
     }
+
+    pub fn release(&mut self) -> io::Result<()> {
+        self.gpio.cleanup();
+        Ok(())
+    }
+
+    pub fn keep_on_drop(&mut self) {
+        // don't reset the pin to its original mode
+        self.gpio.set_clear_on_drop(false);
+    }
+}
+
+pub struct IrqPin {
+    gpio: GPIO,
+    irq_pin: u8,
+}
+
+impl IrqPin {
+    pub fn new(pin_num: u64) -> io::Result<IrqPin> {
+        let pin_num8 = pin_num as u8;
+        let mut gpio = GPIO::new().unwrap();
+        gpio.set_mode(pin_num8, Mode::Input);
+        Ok(IrqPin {
+            gpio,
+            irq_pin: pin_num8,
+        })
+    }
+
+    // Wait for the IRQ to be asserted (low), return false on timeout.
+    // Memory mapped GPIOs have no edge detection, so poll the level.
+    pub fn wait(&mut self, timeout: Duration) -> io::Result<bool> {
+        let started = Instant::now();
+        loop {
+            if self.is_asserted()? {
+                return Ok(true);
+            }
+            if started.elapsed() >= timeout {
+                return Ok(false);
+            }
+            sleep(Duration::from_micros(50));
+        }
+    }
+
+    fn is_asserted(&mut self) -> io::Result<bool> {
+        self.gpio
+            .read(self.irq_pin)
+            .map(|level| level == Level::Low)
+            .map_err(|_| io::Error::other("Unable to read IRQ"))
+    }
+
+    pub fn release(&mut self) -> io::Result<()> {
+        self.gpio.cleanup();
+        Ok(())
+    }
 }
@@ -0,0 +1,357 @@
+// Copyright 2017, Romuald Texier-Marcadé <romualdtm@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A cloneable handle sharing one device between threads through a worker thread.
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{OperatingMode, Packet, NRF24L01};
+
+// How long the worker waits for requests before checking the RX FIFO
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+// Received packets kept until a `recv` asks for them, the oldest are dropped first
+const BUFFER_CAPACITY: usize = 64;
+
+enum Request {
+    Send {
+        address: [u8; 5],
+        data: Vec<u8>,
+        reply: Sender<io::Result<u8>>,
+    },
+    Recv {
+        deadline: Option<Instant>,
+        reply: Sender<io::Result<Packet>>,
+    },
+    PushAckPayload {
+        pipe: u8,
+        data: Vec<u8>,
+        reply: Sender<io::Result<()>>,
+    },
+    Reconfigure {
+        mode: OperatingMode,
+        reply: Sender<io::Result<()>>,
+    },
+}
+
+/// A cloneable handle to a device shared between threads.
+///
+/// The device is owned by a worker thread that serialises the requests of all the
+/// handles, so that no locking is needed. In RX mode, the worker keeps listening
+/// and buffers the incoming packets until a `.recv()` asks for them.
+///
+/// `.send(...)` temporarily switches a receiver to TX mode, then back to listening.
+/// It is refused while ACK payloads are waiting in the TX FIFO, which would be sent
+/// as regular packets.
+///
+/// In supervisor mode (see `NRF24L01::set_supervision`), the worker also restores the
+/// configuration after a brownout.
+///
+/// The worker stops and the device is dropped once all the handles are dropped.
+///
+/// ```rust,no_run
+/// use nrf24l01::{OperatingMode, RXConfig, RadioHandle, NRF24L01};
+/// use std::thread;
+///
+/// let config = RXConfig {
+///     channel: 108,
+///     pipe0_address: *b"abcde",
+///     ..Default::default()
+/// };
+/// let mut device = NRF24L01::new(25, 0).unwrap();
+/// device.configure(&OperatingMode::RX(config)).unwrap();
+/// let radio = RadioHandle::spawn(device).map_err(|(_, err)| err).unwrap();
+/// let commands = radio.clone();
+/// thread::spawn(move || {
+///     commands.send(*b"fghij", b"ping").unwrap();
+/// });
+/// let packet = radio.recv().unwrap();
+/// println!("Received {:?}", packet.payload());
+/// ```
+#[derive(Clone)]
+pub struct RadioHandle {
+    requests: Sender<Request>,
+}
+
+impl RadioHandle {
+    /// Move the device to a new worker thread and return the first handle to it.
+    ///
+    /// In RX mode, the device should be configured already.
+    ///
+    /// # Errors
+    ///
+    /// System IO errors if the thread can't be spawned. The device is handed back
+    /// along with the error.
+    // the device is handed back on failure, by design
+    #[allow(clippy::result_large_err)]
+    pub fn spawn(device: NRF24L01) -> Result<RadioHandle, (NRF24L01, io::Error)> {
+        let (requests, incoming) = mpsc::channel();
+        // the device is handed over once the thread runs, so that it can be given
+        // back if the thread can't be spawned
+        let (hand_over, handed_over) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("nrf24l01-worker".to_string())
+            .spawn(move || {
+                if let Ok(device) = handed_over.recv() {
+                    Worker::new(device).run(incoming)
+                }
+            });
+        if let Err(err) = spawned {
+            return Err((device, err));
+        }
+        if let Err(mpsc::SendError(device)) = hand_over.send(device) {
+            return Err((
+                device,
+                io::Error::other("The radio worker thread has stopped"),
+            ));
+        }
+        Ok(RadioHandle { requests })
+    }
+
+    fn call<T, F>(&self, request: F) -> io::Result<T>
+    where
+        F: FnOnce(Sender<io::Result<T>>) -> Request,
+    {
+        let (reply, response) = mpsc::channel();
+        self.requests.send(request(reply)).map_err(|_| stopped())?;
+        response.recv().map_err(|_| stopped())?
+    }
+
+    /// Send `data` to `address`, see `NRF24L01::send_to`.
+    ///
+    /// A packet that could not be sent is dropped: the next call doesn't send it again.
+    ///
+    /// # Errors
+    ///
+    /// Same as `NRF24L01::send_to`, and a custom io::ErrorKind::BrokenPipe if the
+    /// worker has stopped. In RX mode, a custom io::ErrorKind::WouldBlock if ACK
+    /// payloads are waiting to be sent.
+    pub fn send(&self, address: [u8; 5], data: &[u8]) -> io::Result<u8> {
+        self.call(|reply| Request::Send {
+            address,
+            data: data.to_vec(),
+            reply,
+        })
+    }
+
+    /// Wait for the next packet received.
+    ///
+    /// # Errors
+    ///
+    /// Return Spidev errors and a custom io::ErrorKind::BrokenPipe if the worker
+    /// has stopped.
+    pub fn recv(&self) -> io::Result<Packet> {
+        self.call(|reply| Request::Recv {
+            deadline: None,
+            reply,
+        })
+    }
+
+    /// Wait for the next packet received for at most `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// Same as `.recv()`, and a custom io::ErrorKind::TimedOut if no packet arrives
+    /// in time.
+    pub fn recv_timeout(&self, timeout: Duration) -> io::Result<Packet> {
+        self.call(|reply| Request::Recv {
+            // a timeout too long to be represented never expires
+            deadline: Instant::now().checked_add(timeout),
+            reply,
+        })
+    }
+
+    /// [RX mode only] Queue an ACK payload for the next packet received on `pipe`.
+    ///
+    /// # Errors
+    ///
+    /// Same as `NRF24L01::push`, a custom io::ErrorKind::InvalidInput in TX mode or
+    /// if `pipe` is above 5, and a custom io::ErrorKind::BrokenPipe if the worker
+    /// has stopped.
+    pub fn push_ack_payload(&self, pipe: u8, data: &[u8]) -> io::Result<()> {
+        self.call(|reply| Request::PushAckPayload {
+            pipe,
+            data: data.to_vec(),
+            reply,
+        })
+    }
+
+    /// Configure the device again, see `NRF24L01::configure`.
+    ///
+    /// The packets received and not read yet are dropped. In RX mode, the device
+    /// starts listening right away.
+    ///
+    /// # Errors
+    ///
+    /// Same as `NRF24L01::configure`, and a custom io::ErrorKind::BrokenPipe if the
+    /// worker has stopped.
+    pub fn reconfigure(&self, mode: &OperatingMode) -> io::Result<()> {
+        self.call(|reply| Request::Reconfigure {
+            mode: mode.clone(),
+            reply,
+        })
+    }
+}
+
+fn stopped() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "The radio worker has stopped!")
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "No packet received in time!")
+}
+
+struct Worker {
+    device: NRF24L01,
+    packets: VecDeque<Packet>,
+    waiting: VecDeque<(Option<Instant>, Sender<io::Result<Packet>>)>,
+}
+
+impl Worker {
+    fn new(device: NRF24L01) -> Worker {
+        Worker {
+            device,
+            packets: VecDeque::new(),
+            waiting: VecDeque::new(),
+        }
+    }
+
+    fn run(mut self, incoming: Receiver<Request>) {
+        if self.device.is_receiver() {
+            // errors are reported by the next `recv`
+            let _ = self.device.listen();
+        }
+        loop {
+            match incoming.recv_timeout(POLL_INTERVAL) {
+                Ok(request) => self.handle(request),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            if let Err(err) = self.receive() {
+                // report the error to the waiting `recv`s
+                for (_, reply) in self.waiting.drain(..) {
+                    let _ = reply.send(Err(io::Error::new(err.kind(), err.to_string())));
+                }
+            }
+            self.serve();
+        }
+    }
+
+    fn handle(&mut self, request: Request) {
+        // replies are lost only if the calling handle is gone
+        match request {
+            Request::Send {
+                address,
+                data,
+                reply,
+            } => {
+                let _ = reply.send(self.send(address, &data));
+            }
+            Request::Recv { deadline, reply } => self.waiting.push_back((deadline, reply)),
+            Request::PushAckPayload { pipe, data, reply } => {
+                let result = if self.device.is_receiver() {
+                    self.device.push_ack_payload(pipe, &data)
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Cannot push ACK payloads in TX mode!",
+                    ))
+                };
+                let _ = reply.send(result);
+            }
+            Request::Reconfigure { mode, reply } => {
+                self.packets.clear();
+                let _ = reply.send(self.reconfigure(&mode));
+            }
+        }
+    }
+
+    fn send(&mut self, address: [u8; 5], data: &[u8]) -> io::Result<u8> {
+        if !self.device.is_receiver() {
+            let result = self.device.send_to(address, data);
+            if result.is_err() {
+                // don't leave a failed packet ahead of the next caller's
+                self.device.flush_output()?;
+            }
+            return result;
+        }
+        // ACK payloads would be sent as regular packets
+        if self.device.output_pending()? {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "ACK payloads pending, cannot send now!",
+            ));
+        }
+        let result = self
+            .device
+            .enter_tx()
+            .and_then(|()| self.device.send_to(address, data));
+        // back to listening whatever happened, without a failed packet left to be
+        // sent as an ACK payload
+        let restored = self
+            .device
+            .flush_output()
+            .and_then(|()| self.device.enter_rx())
+            .and_then(|()| self.device.listen());
+        restored.and(result)
+    }
+
+    fn reconfigure(&mut self, mode: &OperatingMode) -> io::Result<()> {
+        self.device.configure(mode)?;
+        if self.device.is_receiver() {
+            self.device.listen()?;
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        // recover from a brownout, in supervisor mode
+        self.device.supervise()?;
+        if !self.device.data_available()? {
+            return Ok(());
+        }
+        let packets = &mut self.packets;
+        // the packets arriving meanwhile are read on the next round
+        self.device.drain_packets(|packet| {
+            if packets.len() == BUFFER_CAPACITY {
+                packets.pop_front();
+            }
+            packets.push_back(packet);
+        })?;
+        Ok(())
+    }
+
+    // Hand the buffered packets to the waiting `recv`s, and time out the expired ones.
+    fn serve(&mut self) {
+        while let Some(packet) = self.packets.pop_front() {
+            match self.waiting.pop_front() {
+                Some((_, reply)) => {
+                    if let Err(mpsc::SendError(Ok(packet))) = reply.send(Ok(packet)) {
+                        // the handle is gone, keep the packet for another one
+                        self.packets.push_front(packet);
+                    }
+                }
+                None => {
+                    self.packets.push_front(packet);
+                    break;
+                }
+            }
+        }
+        let now = Instant::now();
+        self.waiting.retain(|(deadline, reply)| match *deadline {
+            Some(deadline) if deadline <= now => {
+                let _ = reply.send(Err(timed_out()));
+                false
+            }
+            _ => true,
+        });
+    }
+}
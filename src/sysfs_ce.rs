@@ -1,6 +1,7 @@
 extern crate sysfs_gpio;
 
 use std::io;
+use std::time::Duration;
 
 pub struct CEPin {
     ce_pin: sysfs_gpio::Pin,
@@ -12,18 +13,10 @@ impl CEPin {
     // add code here
     pub fn new(pin_num: u64) -> io::Result<CEPin> {
         let ce = sysfs_gpio::Pin::new(pin_num);
-        ce.export().or_else(|_| {
-            Err(io::Error::new(
-                io::ErrorKind::PermissionDenied,
-                "Unable to export CE",
-            ))
-        })?;
-        ce.set_direction(sysfs_gpio::Direction::Low).or_else(|_| {
-            Err(io::Error::new(
-                io::ErrorKind::PermissionDenied,
-                "Unable to set CE",
-            ))
-        })?;
+        ce.export()
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to export CE"))?;
+        ce.set_direction(sysfs_gpio::Direction::Low)
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to set CE"))?;
         Ok(CEPin {
             ce_pin: ce,
             value: 0,
@@ -43,7 +36,7 @@ impl CEPin {
         Ok(())
     }
 
-    pub fn save_state(&mut self) -> () {
+    pub fn save_state(&mut self) {
         self.saved_value = self.value;
     }
 
@@ -52,4 +45,61 @@ impl CEPin {
         self.value = self.saved_value;
         Ok(())
     }
+
+    pub fn release(&mut self) -> io::Result<()> {
+        self.ce_pin
+            .unexport()
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to unexport CE"))
+    }
+
+    pub fn keep_on_drop(&mut self) {
+        // sysfs pins stay exported unless explicitly released
+    }
+}
+
+pub struct IrqPin {
+    irq_pin: sysfs_gpio::Pin,
+    poller: sysfs_gpio::PinPoller,
+}
+
+impl IrqPin {
+    pub fn new(pin_num: u64) -> io::Result<IrqPin> {
+        let irq = sysfs_gpio::Pin::new(pin_num);
+        irq.export()
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to export IRQ"))?;
+        irq.set_direction(sysfs_gpio::Direction::In)
+            .and_then(|_| irq.set_edge(sysfs_gpio::Edge::FallingEdge))
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to set IRQ"))?;
+        let poller = irq
+            .get_poller()
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to poll IRQ"))?;
+        Ok(IrqPin {
+            irq_pin: irq,
+            poller,
+        })
+    }
+
+    // Wait for the IRQ to be asserted (low), return false on timeout.
+    pub fn wait(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.is_asserted()? {
+            return Ok(true);
+        }
+        self.poller
+            .poll(timeout.as_millis() as isize)
+            .map_err(|_| io::Error::other("Unable to poll IRQ"))?;
+        self.is_asserted()
+    }
+
+    fn is_asserted(&self) -> io::Result<bool> {
+        self.irq_pin
+            .get_value()
+            .map(|value| value == 0)
+            .map_err(|_| io::Error::other("Unable to read IRQ"))
+    }
+
+    pub fn release(&mut self) -> io::Result<()> {
+        self.irq_pin
+            .unexport()
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Unable to unexport IRQ"))
+    }
 }
@@ -0,0 +1,294 @@
+// Copyright 2017, Romuald Texier-Marcadé <romualdtm@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A typestate API on top of the `NRF24L01` driver.
+//!
+//! The state of the device is part of the type of the `Radio`, and the transitions
+//! consume the radio and return it in its new state. That way, operations that don't make
+//! sense in the current state, like sending from a receiver, don't compile.
+//!
+//! ```rust,no_run
+//! extern crate nrf24l01;
+//!
+//! use nrf24l01::typestate::Radio;
+//! use nrf24l01::{RXConfig, NRF24L01};
+//!
+//! fn main() {
+//!     let config = RXConfig {
+//!         channel: 108,
+//!         pipe0_address: *b"abcde",
+//!         ..Default::default()
+//!     };
+//!     let device = NRF24L01::new(25, 0).unwrap();
+//!     let radio = Radio::new(device).map_err(|(_, err)| err).unwrap();
+//!     let standby = radio.configure_rx(&config).map_err(|(_, err)| err).unwrap();
+//!     let mut receiver = standby.listen().map_err(|(_, err)| err).unwrap();
+//!     receiver.push_ack_payload(0, b"ack payload").unwrap();
+//! }
+//! ```
+//!
+//! Whereas sending from a receiver is rejected by the compiler:
+//!
+//! ```rust,compile_fail
+//! # extern crate nrf24l01;
+//! # use nrf24l01::typestate::{Radio, Rx};
+//! fn send_from_receiver(receiver: &mut Radio<Rx>) {
+//!     receiver.send().unwrap();
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! On failure, a transition hands the radio back in its previous state along with
+//! the error, so that the device is never lost.
+//!
+//! The dynamic API remains available through `.into_inner()`.
+
+// Failed transitions hand back the radio by design.
+#![allow(clippy::result_large_err)]
+
+use std::io;
+use std::marker::PhantomData;
+
+use std::time::Duration;
+
+use crate::{
+    MaxRetriesPolicy, OperatingMode, Packet, PacketOutcome, RXConfig, SendStatus, StreamReport,
+    TXConfig, NRF24L01,
+};
+
+/// Powered down, not configured.
+pub struct PowerDown;
+/// Configured as receiver, in standby.
+pub struct StandbyRx;
+/// Configured as receiver, actively listening.
+pub struct Rx;
+/// Configured as transmitter, in standby between transmissions.
+pub struct Tx;
+
+/// A `NRF24L01` device in state `S`.
+pub struct Radio<S> {
+    device: NRF24L01,
+    state: PhantomData<S>,
+}
+
+/// The outcome of a transition from state `S` to state `T`.
+///
+/// On failure, the radio is returned in state `S` along with the error.
+pub type Transition<T, S> = Result<Radio<T>, (Radio<S>, io::Error)>;
+
+impl<S> Radio<S> {
+    fn transition<T, F>(mut self, operation: F) -> Transition<T, S>
+    where
+        F: FnOnce(&mut NRF24L01) -> io::Result<()>,
+    {
+        match operation(&mut self.device) {
+            Ok(()) => Ok(Radio {
+                device: self.device,
+                state: PhantomData,
+            }),
+            Err(err) => Err((self, err)),
+        }
+    }
+
+    /// Give back the underlying driver, for the dynamic API.
+    pub fn into_inner(self) -> NRF24L01 {
+        self.device
+    }
+
+    /// Power the device down.
+    ///
+    /// The device must be configured again afterwards.
+    pub fn power_down(self) -> Transition<PowerDown, S> {
+        self.transition(|device| device.power_down())
+    }
+}
+
+impl Radio<PowerDown> {
+    /// Take control of a device and power it down.
+    ///
+    /// On failure, the driver is returned along with the error.
+    pub fn new(mut device: NRF24L01) -> Result<Radio<PowerDown>, (NRF24L01, io::Error)> {
+        match device.power_down() {
+            Ok(()) => Ok(Radio {
+                device,
+                state: PhantomData,
+            }),
+            Err(err) => Err((device, err)),
+        }
+    }
+
+    /// Configure the device as Primary Receiver and power it up.
+    pub fn configure_rx(self, config: &RXConfig) -> Transition<StandbyRx, PowerDown> {
+        let mode = OperatingMode::RX(config.clone());
+        self.transition(|device| device.configure(&mode))
+    }
+
+    /// Configure the device as Primary Transmitter and power it up.
+    pub fn configure_tx(self, config: &TXConfig) -> Transition<Tx, PowerDown> {
+        let mode = OperatingMode::TX(config.clone());
+        self.transition(|device| device.configure(&mode))
+    }
+}
+
+impl Radio<StandbyRx> {
+    /// Start listening.
+    pub fn listen(self) -> Transition<Rx, StandbyRx> {
+        self.transition(|device| device.listen())
+    }
+
+    /// Switch to transmitter, see `NRF24L01::enter_tx`.
+    pub fn enter_tx(self) -> Transition<Tx, StandbyRx> {
+        self.transition(|device| device.enter_tx())
+    }
+
+    /// Is there any incoming data to read?
+    pub fn data_available(&self) -> io::Result<bool> {
+        self.device.data_available()
+    }
+
+    /// Read the received packets, see `NRF24L01::read_all`.
+    pub fn read_all<F>(&mut self, process_packet: F) -> io::Result<u8>
+    where
+        F: FnMut(&[u8]),
+    {
+        self.device.read_all(process_packet)
+    }
+
+    /// Queue an ACK payload for the next packet received on `pipe_num`, see
+    /// `NRF24L01::push`. A `pipe_num` above 5 is an io::ErrorKind::InvalidInput.
+    pub fn push_ack_payload(&mut self, pipe_num: u8, data: &[u8]) -> io::Result<()> {
+        self.device.push_ack_payload(pipe_num, data)
+    }
+
+    /// Clear input queue.
+    pub fn flush_input(&mut self) -> io::Result<()> {
+        self.device.flush_input()
+    }
+
+    /// Clear ACK payloads queue.
+    pub fn flush_output(&mut self) -> io::Result<()> {
+        self.device.flush_output()
+    }
+}
+
+impl Radio<Rx> {
+    /// Stop listening.
+    pub fn standby(self) -> Transition<StandbyRx, Rx> {
+        self.transition(|device| device.standby())
+    }
+
+    /// Is there any incoming data to read?
+    pub fn data_available(&self) -> io::Result<bool> {
+        self.device.data_available()
+    }
+
+    /// Read the received packets, see `NRF24L01::read_all`.
+    pub fn read_all<F>(&mut self, process_packet: F) -> io::Result<u8>
+    where
+        F: FnMut(&[u8]),
+    {
+        self.device.read_all(process_packet)
+    }
+
+    /// Wait for the next packet, see `NRF24L01::recv`.
+    pub fn recv(&mut self) -> io::Result<Packet> {
+        self.device.recv()
+    }
+
+    /// Wait for the next packet for at most `timeout`, see `NRF24L01::recv_timeout`.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Packet> {
+        self.device.recv_timeout(timeout)
+    }
+
+    /// Queue an ACK payload for the next packet received on `pipe_num`, see
+    /// `NRF24L01::push`. A `pipe_num` above 5 is an io::ErrorKind::InvalidInput.
+    pub fn push_ack_payload(&mut self, pipe_num: u8, data: &[u8]) -> io::Result<()> {
+        self.device.push_ack_payload(pipe_num, data)
+    }
+}
+
+impl Radio<Tx> {
+    /// Switch to receiver, see `NRF24L01::enter_rx`.
+    pub fn enter_rx(self) -> Transition<StandbyRx, Tx> {
+        self.transition(|device| device.enter_rx())
+    }
+
+    /// Queue a packet to be sent.
+    pub fn push(&mut self, data: &[u8]) -> io::Result<()> {
+        self.device.push(0, data)
+    }
+
+    /// Queue a packet beyond the capacity of the TX FIFO, see `NRF24L01::enqueue`.
+    pub fn enqueue(&mut self, data: &[u8]) -> io::Result<()> {
+        self.device.enqueue(data)
+    }
+
+    /// Send all queued packets, see `NRF24L01::send`.
+    pub fn send(&mut self) -> io::Result<u8> {
+        self.device.send()
+    }
+
+    /// Send all queued packets, giving up after `timeout`, see `NRF24L01::send_timeout`.
+    pub fn send_timeout(&mut self, timeout: Duration) -> io::Result<u8> {
+        self.device.send_timeout(timeout)
+    }
+
+    /// Send all queued packets, applying `policy` on failure, see
+    /// `NRF24L01::send_with_policy`.
+    pub fn send_with_policy(&mut self, policy: MaxRetriesPolicy) -> io::Result<u8> {
+        self.device.send_with_policy(policy)
+    }
+
+    /// Send all queued packets and report the outcome of each, see
+    /// `NRF24L01::send_detailed`.
+    pub fn send_detailed(&mut self) -> io::Result<Vec<PacketOutcome>> {
+        self.device.send_detailed()
+    }
+
+    /// Send `packets` as fast as possible, see `NRF24L01::stream`.
+    pub fn stream<I, P>(&mut self, packets: I) -> io::Result<StreamReport>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        self.device.stream(packets)
+    }
+
+    /// Start sending all queued packets, see `NRF24L01::start_send`.
+    pub fn start_send(&mut self) -> io::Result<()> {
+        self.device.start_send()
+    }
+
+    /// Check the progress of the transmission, see `NRF24L01::poll_send`.
+    pub fn poll_send(&mut self) -> io::Result<SendStatus> {
+        self.device.poll_send()
+    }
+
+    /// Send a packet to the given destination, see `NRF24L01::send_to`.
+    pub fn send_to(&mut self, address: [u8; 5], data: &[u8]) -> io::Result<u8> {
+        self.device.send_to(address, data)
+    }
+
+    /// Has an ACK payload been received?
+    pub fn data_available(&self) -> io::Result<bool> {
+        self.device.data_available()
+    }
+
+    /// Read the received ACK payloads, see `NRF24L01::read_all`.
+    pub fn read_all<F>(&mut self, process_packet: F) -> io::Result<u8>
+    where
+        F: FnMut(&[u8]),
+    {
+        self.device.read_all(process_packet)
+    }
+
+    /// Clear output queue.
+    pub fn flush_output(&mut self) -> io::Result<()> {
+        self.device.flush_output()
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2017, Romuald Texier-Marcadé <romualdtm@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/license/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option.  This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Typed values in physical units for the configuration fields that are raw register codes.
+
+use std::convert::TryFrom;
+use std::io;
+
+use crate::DataRate;
+
+/// A RF channel, that is a frequency from 2400 MHz to 2525 MHz, in steps of 1 MHz.
+///
+/// Converts into the raw `channel` value expected by `RXConfig` and `TXConfig`:
+///
+/// ```rust
+/// use nrf24l01::{Channel, TXConfig};
+///
+/// let config = TXConfig {
+///     channel: Channel::from_mhz(2476).unwrap().into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(config.channel, 76);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub struct Channel(u8);
+
+impl Channel {
+    /// Channel from its raw value, in the range [0, 125].
+    pub fn new(channel: u8) -> io::Result<Channel> {
+        if channel > 125 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Channel {} is out of range [0, 125]", channel),
+            ))
+        } else {
+            Ok(Channel(channel))
+        }
+    }
+
+    /// Channel from its frequency, in the range [2400, 2525] MHz.
+    pub fn from_mhz(mhz: u16) -> io::Result<Channel> {
+        if !(2400..=2525).contains(&mhz) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Frequency {} MHz is out of range [2400, 2525]", mhz),
+            ))
+        } else {
+            Ok(Channel((mhz - 2400) as u8))
+        }
+    }
+
+    /// Frequency in MHz.
+    pub fn mhz(self) -> u16 {
+        2400 + u16::from(self.0)
+    }
+
+    /// Raw channel value.
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<Channel> for u8 {
+    fn from(channel: Channel) -> u8 {
+        channel.0
+    }
+}
+
+impl TryFrom<u8> for Channel {
+    type Error = io::Error;
+
+    fn try_from(channel: u8) -> io::Result<Channel> {
+        Channel::new(channel)
+    }
+}
+
+/// Delay between retries, from 250 µs to 4000 µs, in steps of 250 µs.
+///
+/// Converts into the raw `retry_delay` value expected by `TXConfig`:
+///
+/// ```rust
+/// use nrf24l01::{RetryDelay, TXConfig};
+///
+/// let config = TXConfig {
+///     retry_delay: RetryDelay::from_micros(750).unwrap().into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(config.retry_delay, 2);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub struct RetryDelay(u8);
+
+impl RetryDelay {
+    /// Delay from its raw value, in the range [0, 15].
+    pub fn new(retry_delay: u8) -> io::Result<RetryDelay> {
+        if retry_delay > 15 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Retry delay {} is out of range [0, 15]", retry_delay),
+            ))
+        } else {
+            Ok(RetryDelay(retry_delay))
+        }
+    }
+
+    /// Delay from its duration in µs, a multiple of 250 in the range [250, 4000].
+    pub fn from_micros(micros: u32) -> io::Result<RetryDelay> {
+        if !(250..=4000).contains(&micros) || micros % 250 != 0 {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Retry delay {} µs is not a multiple of 250 in the range [250, 4000]",
+                    micros
+                ),
+            ))
+        } else {
+            Ok(RetryDelay((micros / 250 - 1) as u8))
+        }
+    }
+
+    /// The shortest delay that is at least `micros` µs long, capped to 4000 µs.
+    pub fn at_least(micros: u32) -> RetryDelay {
+        let steps = micros.div_ceil(250).clamp(1, 16);
+        RetryDelay((steps - 1) as u8)
+    }
+
+    /// The smallest delay leaving enough time for an ACK to arrive.
+    ///
+    /// The receiver needs 130 µs to switch to TX mode, then the ACK packet takes
+    /// 1 byte of preamble, the address, 9 bits of packet control field, the payload
+    /// and the CRC to be transmitted at `data_rate`. The delay is never shorter than
+    /// the datasheet requires (SETUP_RETR and section 7.4.2), which leaves a margin.
+    pub fn minimum(
+        data_rate: DataRate,
+        crc_length: u8,
+        address_width: u8,
+        ack_payload: u8,
+    ) -> RetryDelay {
+        let ack_payload = u32::from(ack_payload.min(32));
+        let bytes = 1 + u32::from(address_width) + ack_payload;
+        let bits = 8 * (bytes + u32::from(crc_length)) + 9;
+        let air_time = match data_rate {
+            DataRate::R250Kbps => bits * 4,
+            DataRate::R1Mbps => bits,
+            DataRate::R2Mbps => bits.div_ceil(2),
+        };
+        let datasheet = match data_rate {
+            DataRate::R250Kbps if ack_payload == 0 => 500,
+            DataRate::R250Kbps => 500 + 250 * ack_payload.div_ceil(8),
+            DataRate::R1Mbps if ack_payload > 5 => 500,
+            DataRate::R2Mbps if ack_payload > 15 => 500,
+            _ => 250,
+        };
+        RetryDelay::at_least((130 + air_time).max(datasheet))
+    }
+
+    /// Duration in µs.
+    pub fn micros(self) -> u32 {
+        250 + 250 * u32::from(self.0)
+    }
+
+    /// Raw retry delay value.
+    pub fn value(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<RetryDelay> for u8 {
+    fn from(retry_delay: RetryDelay) -> u8 {
+        retry_delay.0
+    }
+}
+
+impl TryFrom<u8> for RetryDelay {
+    type Error = io::Error;
+
+    fn try_from(retry_delay: u8) -> io::Result<RetryDelay> {
+        RetryDelay::new(retry_delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_conversions() {
+        let channel = Channel::from_mhz(2476).unwrap();
+        assert_eq!(channel.value(), 76);
+        assert_eq!(channel.mhz(), 2476);
+        assert_eq!(Channel::new(125).unwrap().mhz(), 2525);
+        assert!(Channel::from_mhz(2399).is_err());
+        assert!(Channel::from_mhz(2526).is_err());
+        assert!(Channel::try_from(126).is_err());
+    }
+
+    #[test]
+    fn retry_delay_conversions() {
+        let retry_delay = RetryDelay::from_micros(750).unwrap();
+        assert_eq!(retry_delay.value(), 2);
+        assert_eq!(retry_delay.micros(), 750);
+        assert_eq!(RetryDelay::new(15).unwrap().micros(), 4000);
+        assert!(RetryDelay::from_micros(700).is_err());
+        assert!(RetryDelay::from_micros(0).is_err());
+        assert!(RetryDelay::from_micros(4250).is_err());
+        assert_eq!(RetryDelay::at_least(0).micros(), 250);
+        assert_eq!(RetryDelay::at_least(251).micros(), 500);
+        assert_eq!(RetryDelay::at_least(5000).micros(), 4000);
+    }
+
+    #[test]
+    fn minimum_retry_delay() {
+        let minimum = |data_rate, ack_payload| RetryDelay::minimum(data_rate, 2, 5, ack_payload);
+        assert_eq!(minimum(DataRate::R2Mbps, 15).micros(), 250);
+        assert_eq!(minimum(DataRate::R2Mbps, 32).micros(), 500);
+        assert_eq!(minimum(DataRate::R1Mbps, 0).micros(), 250);
+        assert_eq!(minimum(DataRate::R1Mbps, 32).micros(), 500);
+        assert_eq!(minimum(DataRate::R250Kbps, 0).micros(), 500);
+        assert_eq!(minimum(DataRate::R250Kbps, 32).micros(), 1500);
+    }
+
+    #[test]
+    fn datasheet_retry_delay_thresholds() {
+        let minimum = |data_rate, ack_payload| RetryDelay::minimum(data_rate, 2, 5, ack_payload);
+        assert_eq!(minimum(DataRate::R2Mbps, 16).micros(), 500);
+        assert_eq!(minimum(DataRate::R2Mbps, 20).micros(), 500);
+        assert_eq!(minimum(DataRate::R1Mbps, 5).micros(), 250);
+        assert_eq!(minimum(DataRate::R1Mbps, 6).micros(), 500);
+        assert_eq!(minimum(DataRate::R250Kbps, 8).micros(), 750);
+        assert_eq!(minimum(DataRate::R250Kbps, 9).micros(), 1000);
+        assert_eq!(minimum(DataRate::R250Kbps, 10).micros(), 1000);
+        assert_eq!(minimum(DataRate::R250Kbps, 16).micros(), 1000);
+        assert_eq!(minimum(DataRate::R250Kbps, 17).micros(), 1250);
+        assert_eq!(minimum(DataRate::R250Kbps, 18).micros(), 1250);
+        assert_eq!(minimum(DataRate::R250Kbps, 24).micros(), 1250);
+        assert_eq!(minimum(DataRate::R250Kbps, 25).micros(), 1500);
+    }
+}